@@ -0,0 +1,49 @@
+use async_streams::{calculate_signals, collect_rows, Column, SignalSettings};
+use chrono::{DateTime, Utc};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn many_series(symbols: usize, len: usize) -> Vec<Vec<f64>> {
+    (0..symbols).map(|i| (0..len).map(|n| 100.0 + ((n * (i + 1)) as f64 * 0.01).sin()).collect()).collect()
+}
+
+///
+/// Every column, so that each symbol runs all of the signals a row can hold.
+///
+fn settings() -> SignalSettings {
+    SignalSettings { columns: Column::ALL.to_vec(), sma_window: 50, ..SignalSettings::default() }
+}
+
+fn parallel_signals(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread().build().unwrap();
+    let (start, settings) = (DateTime::<Utc>::UNIX_EPOCH, settings());
+    let mut group = c.benchmark_group("signals");
+    for count in [8, 64] {
+        let series = many_series(count, 50_000);
+        let symbols: Vec<String> = (0..count).map(|i| format!("SYM{}", i)).collect();
+        group.bench_with_input(BenchmarkId::new("on_reactor", count), &series, |b, series| {
+            b.iter(|| {
+                runtime.block_on(async {
+                    let mut rows = Vec::with_capacity(series.len());
+                    for (symbol, s) in symbols.iter().zip(series) {
+                        rows.push(calculate_signals(symbol, &start, s, &settings).await);
+                    }
+                    rows
+                })
+            })
+        });
+        // through the fan-out the stream itself uses, with the fetches already done
+        group.bench_with_input(BenchmarkId::new("collect_rows", count), &series, |b, series| {
+            b.iter(|| {
+                runtime.block_on(collect_rows(&symbols, &start, &settings, false, |symbol| {
+                    let index = symbols.iter().position(|s| *s == symbol).unwrap();
+                    let closes = series[index].clone();
+                    async move { Ok(closes) }
+                }))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parallel_signals);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+use async_streams::{AsyncStockSignal, BollingerBands, Volatility, WindowedSMA};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+///
+/// A deterministic random walk around 100.
+///
+fn closes(len: usize) -> Vec<f64> {
+    let mut seed: u64 = 7;
+    (0..len)
+        .scan(100.0, |price, _| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            *price += ((seed >> 33) as f64 / (1u64 << 31) as f64) - 0.5;
+            Some(*price)
+        })
+        .collect()
+}
+
+fn mean(w: &[f64]) -> f64 {
+    w.iter().sum::<f64>() / w.len() as f64
+}
+
+fn std_dev(w: &[f64]) -> f64 {
+    let m = mean(w);
+    (w.iter().map(|v| (v - m).powi(2)).sum::<f64>() / w.len() as f64).sqrt()
+}
+
+fn rolling_windows(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let series = closes(100_000);
+    let mut group = c.benchmark_group("rolling");
+    for window in [5, 20, 200] {
+        group.bench_with_input(BenchmarkId::new("sma/naive", window), &window, |b, &window| {
+            b.iter(|| series.windows(window).map(mean).collect::<Vec<f64>>())
+        });
+        group.bench_with_input(BenchmarkId::new("sma/rolling", window), &window, |b, &window| {
+            let signal = WindowedSMA::new(window);
+            b.iter(|| runtime.block_on(signal.calculate(&series)))
+        });
+        group.bench_with_input(BenchmarkId::new("volatility/naive", window), &window, |b, &window| {
+            b.iter(|| series.windows(window).map(std_dev).collect::<Vec<f64>>())
+        });
+        group.bench_with_input(BenchmarkId::new("volatility/rolling", window), &window, |b, &window| {
+            let signal = Volatility::new(window);
+            b.iter(|| runtime.block_on(signal.calculate(&series)))
+        });
+        group.bench_with_input(BenchmarkId::new("bollinger/naive", window), &window, |b, &window| {
+            b.iter(|| {
+                series
+                    .windows(window)
+                    .map(|w| {
+                        let (m, s) = (mean(w), std_dev(w));
+                        (m - 2.0 * s, m, m + 2.0 * s)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("bollinger/rolling", window), &window, |b, &window| {
+            let signal = BollingerBands::new(window, 2.0);
+            b.iter(|| runtime.block_on(signal.calculate(&series)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, rolling_windows);
+criterion_main!(benches);
@@ -0,0 +1,77 @@
+use std::fmt;
+use std::str::FromStr;
+use async_streams::{Column, StockRow};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparator {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+impl Comparator {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Above => ">",
+            Self::AtLeast => ">=",
+            Self::Below => "<",
+            Self::AtMost => "<=",
+        }
+    }
+}
+
+///
+/// A bound on a signal, given on the command line as e.g. `rsi>70` or `change<=-5`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub column: Column,
+    pub comparator: Comparator,
+    pub threshold: f64,
+}
+
+impl Alert {
+    ///
+    /// Whether the row's value breaches the bound. A signal which wasn't calculated never does.
+    ///
+    pub fn breached(&self, row: &StockRow) -> bool {
+        let Some(value) = row.value(self.column) else {
+            return false;
+        };
+        match self.comparator {
+            Comparator::Above => value > self.threshold,
+            Comparator::AtLeast => value >= self.threshold,
+            Comparator::Below => value < self.threshold,
+            Comparator::AtMost => value <= self.threshold,
+        }
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.column.name(), self.comparator.symbol(), self.threshold)
+    }
+}
+
+impl FromStr for Alert {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // two character comparators first, so that `>=` isn't read as `>` followed by `=70`
+        let comparators = [Comparator::AtLeast, Comparator::AtMost, Comparator::Above, Comparator::Below];
+        let (comparator, (signal, threshold)) = comparators
+            .iter()
+            .find_map(|comparator| s.split_once(comparator.symbol()).map(|split| (*comparator, split)))
+            .ok_or_else(|| format!("invalid alert '{}', expected SIGNAL>THRESHOLD, e.g. rsi>70", s))?;
+        let column: Column = signal.parse()?;
+        if !column.is_numeric() {
+            return Err(format!("invalid alert '{}', {} isn't a numeric signal", s, column.name()));
+        }
+        let threshold = threshold
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid alert '{}', threshold '{}' isn't a number", s, threshold.trim()))?;
+        Ok(Self { column, comparator, threshold })
+    }
+}
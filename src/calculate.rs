@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use tokio::task::{self, JoinSet};
+use crate::interval::TRADING_DAYS_PER_YEAR;
+use crate::{AnnualizedVolatility, AsyncStockSignal, Column, CumulativeReturn, FetchError, MinMax, PriceDifference, Rsi, StockRow, WindowedSMA};
+
+///
+/// What to calculate for each symbol and the constants the signals are tuned with.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalSettings {
+    pub columns: Vec<Column>,
+    pub sma_window: usize,
+    pub rsi_period: usize,
+    pub trading_days_per_year: f64,
+    /// The bars in a year at the interval and resample period of the run, what the volatility is annualized with.
+    pub periods_per_year: f64,
+    pub risk_free_rate: f64,
+    /// The zone the period start is labelled in, UTC when unset.
+    pub timezone: Option<Tz>,
+}
+
+impl Default for SignalSettings {
+    fn default() -> Self {
+        Self {
+            columns: Column::DEFAULT.to_vec(),
+            sma_window: 3,
+            rsi_period: 14,
+            trading_days_per_year: TRADING_DAYS_PER_YEAR,
+            periods_per_year: TRADING_DAYS_PER_YEAR,
+            risk_free_rate: 0.0,
+            timezone: None,
+        }
+    }
+}
+
+///
+/// Calculate the signals behind the selected columns. Signals not selected, or which couldn't be calculated from the
+/// closes, are `None`.
+///
+pub async fn calculate_signals(symbol: &str, start: &DateTime<Utc>, closes: &[f64], settings: &SignalSettings) -> StockRow {
+    let selected = |column: Column| settings.columns.contains(&column);
+    let (period_min, period_max) = if selected(Column::Min) || selected(Column::Max) {
+        let signal = MinMax;
+        let range = signal.calculate(closes).await;
+        (range.filter(|_| selected(Column::Min)).map(|(min, _)| min), range.filter(|_| selected(Column::Max)).map(|(_, max)| max))
+    } else {
+        (None, None)
+    };
+    let sma = if selected(Column::Sma) {
+        let signal = WindowedSMA::new(settings.sma_window);
+        signal.calculate(closes).await.unwrap_or_default()
+    } else {
+        vec![]
+    };
+    let pct_change = if selected(Column::Change) {
+        let signal = PriceDifference {};
+        signal.calculate(closes).await.map(|change| change.relative * 100.0)
+    } else {
+        None
+    };
+    let cum_return = if selected(Column::CumReturn) {
+        let signal = CumulativeReturn {};
+        signal.calculate(closes).await
+    } else {
+        None
+    };
+    let annualized_vol = if selected(Column::AnnualizedVol) {
+        let signal = AnnualizedVolatility::new(settings.periods_per_year);
+        signal.calculate(closes).await
+    } else {
+        None
+    };
+    let rsi = if selected(Column::Rsi) {
+        let signal = Rsi::new(settings.rsi_period);
+        signal.calculate(closes).await.and_then(|rsi| rsi.last().copied())
+    } else {
+        None
+    };
+    let last_price = *closes.last().unwrap_or(&0.0);
+    let last_sma = sma.last().copied();
+    let date = match settings.timezone {
+        Some(zone) => start.with_timezone(&zone).to_rfc3339(),
+        None => start.to_rfc3339(),
+    };
+
+    StockRow {
+        period_start: date,
+        symbol: symbol.to_string(),
+        price: last_price,
+        pct_change,
+        period_min,
+        period_max,
+        last_sma,
+        cum_return,
+        annualized_vol,
+        rsi,
+        rel_strength: None,
+        last_quote: None,
+        currency: None,
+    }
+}
+
+///
+/// Calculate the signals on a blocking thread, so that the folds over long histories run off the reactor and overlap
+/// the fetches still in flight.
+///
+pub async fn calculate_signals_blocking(
+    symbol: String,
+    start: DateTime<Utc>,
+    closes: Vec<f64>,
+    settings: Arc<SignalSettings>,
+) -> Result<StockRow, task::JoinError> {
+    let runtime = tokio::runtime::Handle::current();
+    task::spawn_blocking(move || runtime.block_on(calculate_signals(&symbol, &start, &closes, &settings))).await
+}
+
+///
+/// Fetch and calculate the signals for all symbols concurrently. Each symbol's row, or why it has none, is keyed by
+/// its position in `symbols` so that the output order never depends on which fetch completed first. Failed fetches are
+/// reported as they complete; symbols without data fail with `FetchError::Empty`, and those whose fetch or calculation
+/// panicked with `FetchError::Internal`. With `fail_fast` the fetches still in flight are abandoned at the first failure
+/// other than `Empty`.
+///
+pub async fn collect_rows<F, Fut>(
+    symbols: &[String],
+    start: &DateTime<Utc>,
+    settings: &SignalSettings,
+    fail_fast: bool,
+    fetch: F,
+) -> BTreeMap<usize, Result<StockRow, FetchError>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<f64>, FetchError>> + Send + 'static,
+{
+    let settings = Arc::new(settings.clone());
+    let mut fetches = JoinSet::new();
+    for (index, symbol) in symbols.iter().enumerate() {
+        let closes = fetch(symbol.clone());
+        let (symbol, start, settings) = (symbol.clone(), *start, settings.clone());
+        // the fetch runs in a task of its own so that a panic in it is still tied to the symbol's index, in a set so
+        // that it is aborted along with the outer task
+        fetches.spawn(async move {
+            let mut fetch = JoinSet::new();
+            fetch.spawn(closes);
+            let row = match fetch.join_next().await.expect("a fetch was spawned") {
+                Ok(Ok(closes)) if closes.is_empty() => Err(FetchError::Empty),
+                Ok(Ok(closes)) => calculate_signals_blocking(symbol, start, closes, settings)
+                    .await
+                    .map_err(|e| FetchError::Internal(format!("signal calculation failed: {}", e))),
+                Ok(Err(e)) => Err(e),
+                Err(e) => Err(FetchError::Internal(format!("fetch task failed: {}", e))),
+            };
+            (index, row)
+        });
+    }
+
+    let mut rows = BTreeMap::new();
+    while let Some(fetched) = fetches.join_next().await {
+        // only awaiting the other tasks, the outer one doesn't panic and is never aborted while it is joined
+        let Ok((index, row)) = fetched else {
+            continue;
+        };
+        let symbol = &symbols[index];
+        match row {
+            Ok(row) => {
+                rows.insert(index, Ok(row));
+            }
+            Err(FetchError::Empty) => {
+                rows.insert(index, Err(FetchError::Empty));
+            }
+            Err(e) => {
+                report_fetch_error(symbol, &e);
+                rows.insert(index, Err(e));
+                if fail_fast {
+                    break;
+                }
+            }
+        }
+    }
+    rows
+}
+
+///
+/// Report a symbol's failed fetch on stderr.
+///
+pub fn report_fetch_error(symbol: &str, e: &FetchError) {
+    match e {
+        FetchError::TimedOut => eprintln!("\n{} fetch timed out", symbol),
+        FetchError::NotFound => eprintln!("\n{} data not found", symbol),
+        e => eprintln!("\n{}: {}", symbol, e),
+    }
+}
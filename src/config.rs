@@ -0,0 +1,68 @@
+use std::io::Write;
+use serde::Deserialize;
+
+///
+/// Settings read from an optional TOML file. Every field is optional and command line flags take precedence.
+///
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub symbols: Option<Vec<String>>,
+    pub sma_window: Option<usize>,
+    pub risk_free_rate: Option<f64>,
+    pub trading_days_per_year: Option<f64>,
+}
+
+impl Config {
+    pub fn parse(toml: &str) -> Result<Self, String> {
+        toml::from_str(toml).map_err(|e| e.to_string())
+    }
+
+    ///
+    /// Load the config from `path`; used as the value parser of the `--config` flag.
+    ///
+    pub fn load(path: &str) -> Result<Self, String> {
+        let toml = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&toml).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    ///
+    /// A commented config file setting every key to its value here, which should be the defaults.
+    ///
+    pub fn sample(&self) -> String {
+        let mut toml = String::from("# Settings for async_streams, loaded with --config. Every key is optional and flags given on the\n");
+        toml.push_str("# command line take precedence.\n");
+        if let Some(symbols) = &self.symbols {
+            let quoted: Vec<String> = symbols.iter().map(|symbol| format!("{:?}", symbol)).collect();
+            toml.push_str(&format!("\n# Symbols to fetch, replaced by --symbols\nsymbols = [{}]\n", quoted.join(", ")));
+        }
+        if let Some(window) = self.sma_window {
+            toml.push_str(&format!("\n# Number of closes averaged for the sma column, replaced by --sma-window\nsma_window = {}\n", window));
+        }
+        if let Some(rate) = self.risk_free_rate {
+            toml.push_str(&format!("\n# Annual risk free rate, as a fraction\nrisk_free_rate = {:?}\n", rate));
+        }
+        if let Some(days) = self.trading_days_per_year {
+            toml.push_str(&format!("\n# Trading days in a year, used to annualize daily figures\ntrading_days_per_year = {:?}\n", days));
+        }
+        toml
+    }
+
+    ///
+    /// Write the sample config to `path`, refusing to replace an existing file unless `force`.
+    ///
+    pub fn init(&self, path: &str, force: bool) -> Result<(), String> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true);
+        if force {
+            options.create(true).truncate(true);
+        } else {
+            options.create_new(true);
+        }
+        let mut file = options.open(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => format!("{} already exists, pass --force to overwrite it", path),
+            _ => format!("{}: {}", path, e),
+        })?;
+        file.write_all(self.sample().as_bytes()).map_err(|e| format!("{}: {}", path, e))
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeSet;
+use serde::Serialize;
+use async_streams::Ohlcv;
+
+///
+/// Pearson correlations between the daily returns of each pair of symbols, labelled by symbol in both directions.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CorrelationMatrix {
+    pub symbols: Vec<String>,
+    /// The number of returns each correlation is over: those between consecutive days every symbol traded.
+    pub observations: usize,
+    pub values: Vec<Vec<f64>>,
+}
+
+impl CorrelationMatrix {
+    ///
+    /// The matrix of the symbols' returns over the days all of them have a close, i.e. an inner join on the day of the
+    /// timestamps. Symbols are taken longest history first and any which would leave fewer than `min_observations`
+    /// returns in common is excluded instead.
+    ///
+    /// # Returns
+    ///
+    /// The matrix, in the order the symbols were given, and the excluded symbols.
+    ///
+    pub fn calculate(series: &[(String, Vec<Ohlcv>)], min_observations: usize) -> (Self, Vec<String>) {
+        let days: Vec<BTreeSet<u64>> = series.iter().map(|(_, bars)| bars.iter().map(|bar| bar.timestamp / 86_400).collect()).collect();
+        let mut by_length: Vec<usize> = (0..series.len()).collect();
+        by_length.sort_by_key(|i| std::cmp::Reverse(days[*i].len()));
+
+        let mut common: Option<BTreeSet<u64>> = None;
+        let mut included = vec![false; series.len()];
+        for i in by_length {
+            let joined: BTreeSet<u64> = match &common {
+                Some(common) => common.intersection(&days[i]).copied().collect(),
+                None => days[i].clone(),
+            };
+            if joined.len() > min_observations {
+                common = Some(joined);
+                included[i] = true;
+            }
+        }
+        let common = common.unwrap_or_default();
+        let excluded = series.iter().zip(&included).filter(|(_, included)| !**included).map(|((symbol, _), _)| symbol.clone()).collect();
+
+        let (symbols, returns): (Vec<String>, Vec<Vec<f64>>) = series
+            .iter()
+            .zip(&included)
+            .filter(|(_, included)| **included)
+            .map(|((symbol, bars), _)| {
+                let closes: Vec<f64> = bars.iter().filter(|bar| common.contains(&(bar.timestamp / 86_400))).map(|bar| bar.close).collect();
+                (symbol.clone(), closes.windows(2).map(|pair| pair[1] / pair[0] - 1.0).collect())
+            })
+            .unzip();
+        let values = returns.iter().map(|a| returns.iter().map(|b| pearson(a, b)).collect()).collect();
+        (Self { symbols, observations: common.len().saturating_sub(1), values }, excluded)
+    }
+
+    ///
+    /// The matrix as CSV with the symbols along the header and down the first column.
+    ///
+    pub fn to_csv(&self, precision: usize) -> String {
+        let mut csv = std::iter::once(String::new()).chain(self.symbols.iter().cloned()).collect::<Vec<_>>().join(",");
+        for (symbol, row) in self.symbols.iter().zip(&self.values) {
+            csv.push('\n');
+            csv.push_str(symbol);
+            for value in row {
+                csv.push_str(&format!(",{:.*}", precision, value));
+            }
+        }
+        csv.push('\n');
+        csv
+    }
+}
+
+///
+/// Pearson correlation of two equally long series; NaN when either doesn't vary.
+///
+pub fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let (mean_a, mean_b) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let covariance: f64 = a.iter().zip(b).map(|(a, b)| (a - mean_a) * (b - mean_b)).sum();
+    let spread_a: f64 = a.iter().map(|a| (a - mean_a).powi(2)).sum();
+    let spread_b: f64 = b.iter().map(|b| (b - mean_b).powi(2)).sum();
+    covariance / (spread_a * spread_b).sqrt()
+}
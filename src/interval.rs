@@ -0,0 +1,108 @@
+use std::fmt;
+use std::str::FromStr;
+use chrono::TimeDelta;
+
+///
+/// Trading days in a year, used to annualize daily figures.
+///
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+///
+/// The spacing of the bars requested from the data source, written as Yahoo does, e.g. `1d` or `15m`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interval {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    OneHour,
+    #[default]
+    OneDay,
+    OneWeek,
+    OneMonth,
+}
+
+impl Interval {
+    pub const ALL: [Interval; 8] = [
+        Self::OneMinute,
+        Self::FiveMinutes,
+        Self::FifteenMinutes,
+        Self::ThirtyMinutes,
+        Self::OneHour,
+        Self::OneDay,
+        Self::OneWeek,
+        Self::OneMonth,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OneMinute => "1m",
+            Self::FiveMinutes => "5m",
+            Self::FifteenMinutes => "15m",
+            Self::ThirtyMinutes => "30m",
+            Self::OneHour => "1h",
+            Self::OneDay => "1d",
+            Self::OneWeek => "1wk",
+            Self::OneMonth => "1mo",
+        }
+    }
+
+    ///
+    /// Whether the bars are finer than a day.
+    ///
+    pub fn is_intraday(&self) -> bool {
+        !matches!(self, Self::OneDay | Self::OneWeek | Self::OneMonth)
+    }
+
+    ///
+    /// The number of bars in a year, that per bar figures such as the volatility of returns are annualized with: the
+    /// trading days in a year for daily bars, and for intraday bars those of the 6.5 hour regular session on each.
+    ///
+    pub fn annualization_factor(&self) -> f64 {
+        let bars_per_day = match self {
+            Self::OneMinute => 390.0,
+            Self::FiveMinutes => 78.0,
+            Self::FifteenMinutes => 26.0,
+            Self::ThirtyMinutes => 13.0,
+            Self::OneHour => 6.5,
+            Self::OneDay => 1.0,
+            Self::OneWeek => return 52.0,
+            Self::OneMonth => return 12.0,
+        };
+        bars_per_day * TRADING_DAYS_PER_YEAR
+    }
+
+    ///
+    /// How far back a run without a start date looks: enough bars for the windowed signals without fetching more
+    /// than Yahoo serves at that interval.
+    ///
+    pub fn default_lookback(&self) -> TimeDelta {
+        match self {
+            Self::OneMinute => TimeDelta::days(1),
+            Self::FiveMinutes | Self::FifteenMinutes | Self::ThirtyMinutes => TimeDelta::days(5),
+            Self::OneHour => TimeDelta::days(30),
+            Self::OneDay => TimeDelta::weeks(2),
+            Self::OneWeek => TimeDelta::weeks(52),
+            Self::OneMonth => TimeDelta::days(5 * 365),
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        Self::ALL.into_iter().find(|interval| interval.as_str() == s).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(Interval::as_str).collect();
+            format!("unknown interval '{}', expected one of: {}", s, names.join(", "))
+        })
+    }
+}
@@ -0,0 +1,1101 @@
+pub mod signals;
+pub mod provider;
+pub mod sink;
+pub mod resample;
+pub mod interval;
+pub mod price_source;
+pub mod calculate;
+mod stock_row;
+
+//--------------------------------------------------------------------------------------------------
+pub use signals::{
+    AsyncStockSignal,
+    AsyncOhlcSignal,
+    AsyncPairSignal,
+    Ohlcv,
+    PriceDifference,
+    PriceChange,
+    WindowedSMA,
+    MaxPrice,
+    MinPrice,
+    CumulativeReturn,
+    ZScore,
+    WilliamsR,
+    Ema,
+    Trix,
+    Cci,
+    Atr,
+    KeltnerChannels,
+    DonchianChannel,
+    AnnualizedVolatility,
+    ParabolicSar,
+    LinRegSlope,
+    Volatility,
+    BollingerBands,
+    ChaikinMoneyFlow,
+    Rsi,
+    SignalSpec,
+    MoneyFlowIndex,
+    Dpo,
+    Aroon,
+    HullMA,
+    RateOfChange,
+    CoppockCurve,
+    UlcerIndex,
+    Twap,
+    RollingBeta,
+    WeightedMA,
+    StdErrorBands,
+    SchaffTrendCycle,
+    ForceIndex,
+    MinMax,
+    ElderRay,
+    Kama,
+    Vortex,
+    Ppo,
+    MassIndex,
+    Cmo,
+};
+pub use provider::{QuoteProvider, YahooProvider, CsvProvider, Quote, FetchError, FetchTelemetry};
+pub use sink::{OutputSink, CsvSink, JsonSink, TableSink, ParquetSink, SqliteSink};
+pub use stock_row::{StockRow, Column};
+pub use resample::Resample;
+pub use interval::Interval;
+pub use price_source::PriceSource;
+pub use calculate::{SignalSettings, calculate_signals, calculate_signals_blocking, collect_rows, report_fetch_error};
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_price_difference_calculate() {
+        use crate::signals::{PriceChange, PriceDifference};
+
+        let signal = PriceDifference {};
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0]).await, Some(PriceChange { absolute: 0.0, relative: 0.0 }));
+        assert_eq!(signal.calculate(&[1.0, 0.0]).await, Some(PriceChange { absolute: -1.0, relative: -1.0 }));
+        assert_eq!(
+            signal.calculate(&[2.0, 3.0, 5.0, 6.0, 1.0, 2.0, 10.0]).await,
+            Some(PriceChange { absolute: 8.0, relative: 4.0 })
+        );
+        assert_eq!(
+            signal.calculate(&[0.0, 3.0, 5.0, 6.0, 1.0, 2.0, 1.0]).await,
+            Some(PriceChange { absolute: 1.0, relative: 1.0 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_min_price_calculate() {
+        use crate::signals::MinPrice;
+
+        let signal = MinPrice {};
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0]).await, Some(1.0));
+        assert_eq!(signal.calculate(&[1.0, 0.0]).await, Some(0.0));
+        assert_eq!(
+            signal.calculate(&[2.0, 3.0, 5.0, 6.0, 1.0, 2.0, 10.0]).await,
+            Some(1.0)
+        );
+        assert_eq!(
+            signal.calculate(&[0.0, 3.0, 5.0, 6.0, 1.0, 2.0, 1.0]).await,
+            Some(0.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_max_price_calculate() {
+        use crate::signals::MaxPrice;
+
+        let signal = MaxPrice {};
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0]).await, Some(1.0));
+        assert_eq!(signal.calculate(&[1.0, 0.0]).await, Some(1.0));
+        assert_eq!(
+            signal.calculate(&[2.0, 3.0, 5.0, 6.0, 1.0, 2.0, 10.0]).await,
+            Some(10.0)
+        );
+        assert_eq!(
+            signal.calculate(&[0.0, 3.0, 5.0, 6.0, 1.0, 2.0, 1.0]).await,
+            Some(6.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_windowed_sma_calculate() {
+        use crate::signals::WindowedSMA;
+
+        let series = vec![2.0, 4.5, 5.3, 6.5, 4.7];
+
+        let signal = WindowedSMA::new(3);
+        assert_eq!(
+            signal.calculate(&series).await,
+            Some(vec![3.9333333333333336, 5.433333333333334, 5.5])
+        );
+
+        let signal = WindowedSMA::new( 5 );
+        assert_eq!(signal.calculate(&series).await, Some(vec![4.6]));
+
+        let signal = WindowedSMA::new( 10 );
+        assert_eq!(signal.calculate(&series).await, Some(vec![]));
+    }
+
+    #[test]
+    fn test_windowed_sma_window() {
+        assert_eq!(WindowedSMA::new(3).window(), 3);
+        assert_eq!(WindowedSMA::new(30).window(), 30);
+    }
+
+    #[tokio::test]
+    async fn test_zscore_calculate() {
+        use crate::signals::ZScore;
+
+        let signal = ZScore::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(ZScore::new(1).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, Some(vec![]));
+
+        // a constant window has no dispersion
+        assert_eq!(signal.calculate(&[5.0, 5.0, 5.0, 5.0]).await, Some(vec![0.0, 0.0]));
+        // and stays without any once the rolled deviation has been through a move
+        assert_eq!(signal.calculate(&[1.3, 2.7, 5.1, 5.1, 5.1, 5.1]).await.unwrap()[2..], [0.0, 0.0]);
+
+        let z = signal.calculate(&[2.0, 4.0, 6.0]).await.unwrap();
+        assert_eq!(z.len(), 1);
+        assert!((z[0] - 1.224744871391589).abs() < 1e-12);
+
+        let signal = ZScore::new(10);
+        let mut series = vec![10.0, 10.1, 9.9, 10.0, 10.1, 9.9, 10.0, 10.1, 9.9];
+        series.push(20.0);
+        let z = signal.calculate(&series).await.unwrap();
+        assert_eq!(z.len(), 1);
+        assert!(z[0] > 2.5);
+    }
+
+    #[tokio::test]
+    async fn test_williams_r_calculate() {
+        use crate::signals::{AsyncOhlcSignal, Ohlcv, WilliamsR};
+
+        let signal = WilliamsR::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(WilliamsR::new(0).calculate(&[1.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 3.0, 2.0, 4.0]).await, Some(vec![-50.0, 0.0]));
+
+        // a flat window has no range to be placed in
+        assert_eq!(signal.calculate(&[2.0, 2.0, 2.0]).await, Some(vec![0.0]));
+
+        let bar = |high: f64, low: f64, close: f64| Ohlcv { timestamp: 0, open: close, high, low, close, volume: 0 };
+        let bars = vec![bar(10.0, 8.0, 9.0), bar(12.0, 9.0, 11.0), bar(11.0, 6.0, 7.0), bar(9.0, 7.0, 8.5)];
+        let wr = signal.calculate_ohlc(&bars).await.unwrap();
+        assert_eq!(wr, vec![-83.33333333333333, -58.333333333333336]);
+        assert!(wr.iter().all(|v| (-100.0..=0.0).contains(v)));
+    }
+
+    #[tokio::test]
+    async fn test_cumulative_return_calculate() {
+        use crate::signals::CumulativeReturn;
+
+        let signal = CumulativeReturn {};
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[5.0]).await, Some(0.0));
+        assert_eq!(signal.calculate(&[10.0, 12.0, 20.0]).await, Some(1.0));
+        assert_eq!(signal.calculate(&[10.0, 7.0, 5.0]).await, Some(-0.5));
+        assert_eq!(signal.calculate(&[0.0, 1.0, 2.0]).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_ema_calculate() {
+        use crate::signals::Ema;
+
+        let signal = Ema::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(Ema::new(0).calculate(&[1.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, Some(vec![]));
+        assert_eq!(signal.calculate(&[2.0, 4.0, 6.0, 8.0, 2.0]).await, Some(vec![4.0, 6.0, 4.0]));
+    }
+
+    #[tokio::test]
+    async fn test_trix_calculate() {
+        use crate::signals::Trix;
+
+        let signal = Trix::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0; 7]).await, None);
+
+        let series: Vec<f64> = (1..=20).map(|v| v as f64).collect();
+        let trix = signal.calculate(&series).await.unwrap();
+        assert_eq!(trix.len(), series.len() - 3 * 2 - 1);
+        assert!(trix.iter().all(|v| *v > 0.0));
+
+        assert_eq!(signal.calculate(&[5.0; 12]).await, Some(vec![0.0; 5]));
+    }
+
+    #[tokio::test]
+    async fn test_cci_calculate() {
+        use crate::signals::{AsyncOhlcSignal, Cci, Ohlcv};
+
+        let signal = Cci::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[4.0, 4.0, 4.0]).await, Some(vec![0.0]));
+
+        // typical prices of 10, 12, 14 and 12: sma 12, mad 4/3 => cci 100 then sma 12.67, mad 0.89 => cci -50
+        let bar = |tp: f64| Ohlcv { timestamp: 0, open: tp, high: tp + 1.0, low: tp - 1.0, close: tp, volume: 0 };
+        let bars: Vec<Ohlcv> = [10.0, 12.0, 14.0, 12.0].iter().map(|tp| bar(*tp)).collect();
+        let cci = signal.calculate_ohlc(&bars).await.unwrap();
+        assert_eq!(cci.len(), 2);
+        assert!((cci[0] - 100.0).abs() < 1e-9);
+        assert!((cci[1] + 50.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_atr_calculate() {
+        use crate::signals::{AsyncOhlcSignal, Atr, Ohlcv};
+
+        let bar = |high: f64, low: f64, close: f64| Ohlcv { timestamp: 0, open: close, high, low, close, volume: 0 };
+        let signal = Atr::new(2);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(Atr::new(0).calculate_ohlc(&[bar(2.0, 1.0, 1.5)]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(2.0, 1.0, 1.5)]).await, Some(vec![]));
+
+        // true ranges: 2.0 (range), 3.0 (gap up from 2.5), 1.0 (range)
+        let bars = vec![bar(2.0, 1.0, 1.5), bar(3.0, 1.0, 2.5), bar(5.5, 5.0, 5.0), bar(5.0, 4.0, 4.5)];
+        assert_eq!(signal.calculate_ohlc(&bars).await, Some(vec![2.5, 1.75]));
+    }
+
+    #[tokio::test]
+    async fn test_keltner_channels_calculate() {
+        use crate::signals::{AsyncOhlcSignal, KeltnerChannels, Ohlcv};
+
+        let signal = KeltnerChannels::new(3, 2, 2.0);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+
+        let bars: Vec<Ohlcv> = (0..10)
+            .map(|i| {
+                let close = 10.0 + (i as f64).sin();
+                Ohlcv { timestamp: i, open: close, high: close + 1.0, low: close - 0.5, close, volume: 0 }
+            })
+            .collect();
+        let channels = signal.calculate_ohlc(&bars).await.unwrap();
+        assert_eq!(channels.len(), 8);
+        for (lower, middle, upper) in channels {
+            assert!(lower < middle && middle < upper);
+            assert!(((upper - middle) - (middle - lower)).abs() < 1e-12);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_donchian_channel_calculate() {
+        use crate::signals::{AsyncOhlcSignal, DonchianChannel, Ohlcv};
+
+        let signal = DonchianChannel::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(DonchianChannel::new(0).calculate(&[1.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, Some(vec![]));
+        assert_eq!(
+            signal.calculate(&[1.0, 2.0, 3.0, 4.0, 5.0]).await,
+            Some(vec![(1.0, 3.0), (2.0, 4.0), (3.0, 5.0)])
+        );
+
+        let bars: Vec<Ohlcv> = (1..=4)
+            .map(|i| {
+                let close = i as f64;
+                Ohlcv { timestamp: i, open: close, high: close + 0.5, low: close - 0.5, close, volume: 0 }
+            })
+            .collect();
+        assert_eq!(signal.calculate_ohlc(&bars).await, Some(vec![(0.5, 3.5), (1.5, 4.5)]));
+    }
+
+    #[tokio::test]
+    async fn test_annualized_volatility_calculate() {
+        use crate::signals::AnnualizedVolatility;
+
+        let signal = AnnualizedVolatility::new(252.0);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 0.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[3.0, 3.0, 3.0]).await, Some(0.0));
+
+        // daily log returns alternate +/-1% so the daily volatility is exactly 1%
+        let series: Vec<f64> = (0..=20).map(|i| 100.0 * (if i % 2 == 0 { 0.0 } else { 0.01f64 }).exp()).collect();
+        let daily = AnnualizedVolatility::new(1.0).calculate(&series).await.unwrap();
+        assert!((daily - 0.01).abs() < 1e-12);
+        let annual = signal.calculate(&series).await.unwrap();
+        assert!((annual - 0.01 * 252f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_parabolic_sar_calculate() {
+        use crate::signals::{AsyncOhlcSignal, Ohlcv, ParabolicSar};
+
+        let bar = |i: u64, high: f64, low: f64| {
+            let close = (high + low) / 2.0;
+            Ohlcv { timestamp: i, open: close, high, low, close, volume: 0 }
+        };
+        let signal = ParabolicSar::new(0.02, 0.2);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(0, 10.0, 9.0)]).await, None);
+
+        // three rising bars, then a collapse through the SAR on the fifth bar
+        let bars = [
+            bar(0, 10.0, 9.0),
+            bar(1, 11.0, 10.0),
+            bar(2, 12.0, 11.0),
+            bar(3, 13.0, 12.0),
+            bar(4, 9.0, 8.0),
+            bar(5, 8.5, 7.0),
+        ];
+        let expected = [9.0, 9.0, 9.12, 13.0, 13.0];
+        let sars = signal.calculate_ohlc(&bars).await.unwrap();
+        assert_eq!(sars.len(), expected.len());
+        for (sar, expected) in sars.iter().zip(expected) {
+            assert!((sar - expected).abs() < 1e-12, "{} != {}", sar, expected);
+        }
+        // below the lows while rising, above the highs once the trend flips
+        assert!(sars[..3].iter().zip(&bars[1..4]).all(|(sar, bar)| *sar < bar.low));
+        assert!(sars[3..].iter().zip(&bars[4..]).all(|(sar, bar)| *sar > bar.high));
+
+        // the acceleration factor is capped at max_step
+        let bars: Vec<Ohlcv> = (0..30).map(|i| bar(i, 10.0 + i as f64, 9.0 + i as f64)).collect();
+        let sars = ParabolicSar::new(0.1, 0.2).calculate_ohlc(&bars).await.unwrap();
+        let (last, prev) = (sars[sars.len() - 1], sars[sars.len() - 2]);
+        assert!((last - (prev + 0.2 * (bars[28].high - prev))).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_linreg_slope_calculate() {
+        use crate::signals::LinRegSlope;
+
+        let signal = LinRegSlope::new(4);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(LinRegSlope::new(1).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, Some(vec![]));
+
+        let linear: Vec<f64> = (0..10).map(|i| 5.0 + 0.75 * i as f64).collect();
+        let slopes = signal.calculate(&linear).await.unwrap();
+        assert_eq!(slopes.len(), 7);
+        assert!(slopes.iter().all(|slope| (slope - 0.75).abs() < 1e-12));
+
+        let falling: Vec<f64> = linear.iter().rev().cloned().collect();
+        let slopes = LinRegSlope::new(2).calculate(&falling).await.unwrap();
+        assert!(slopes.iter().all(|slope| (slope + 0.75).abs() < 1e-12));
+
+        assert_eq!(signal.calculate(&[3.0; 6]).await, Some(vec![0.0; 3]));
+    }
+
+    #[tokio::test]
+    async fn test_volatility_calculate() {
+        use crate::signals::Volatility;
+
+        let signal = Volatility::new(2);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(Volatility::new(1).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(Volatility::new(3).calculate(&[1.0, 2.0]).await, Some(vec![]));
+        assert_eq!(signal.calculate(&[1.0, 3.0, 3.0, 7.0]).await, Some(vec![1.0, 0.0, 2.0]));
+    }
+
+    #[tokio::test]
+    async fn test_bollinger_bands_calculate() {
+        use crate::signals::BollingerBands;
+
+        let signal = BollingerBands::new(2, 2.0);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(BollingerBands::new(1, 2.0).calculate(&[1.0]).await, None);
+        assert_eq!(
+            signal.calculate(&[1.0, 3.0, 3.0, 7.0]).await,
+            Some(vec![(0.0, 2.0, 4.0), (3.0, 3.0, 3.0), (1.0, 5.0, 9.0)])
+        );
+    }
+
+    #[test]
+    fn test_rolling() {
+        use crate::signals::{rolled_mean_std, rolled_sum, rolling};
+
+        let series = [1.0, 3.0, 3.0, 7.0];
+        assert_eq!(rolling(&[], 2, |w, _| w.len()), None);
+        assert_eq!(rolling(&series, 0, |w, _| w.len()), None);
+        assert_eq!(rolling(&series, 5, |w, _| w.len()), Some(vec![]));
+        assert_eq!(rolling(&series, 4, |w, _| w.len()), Some(vec![4]));
+        // each window after the first is handed the value it lost
+        assert_eq!(rolling(&series, 2, |w, dropped| (w.to_vec(), dropped)), Some(vec![
+            (vec![1.0, 3.0], None),
+            (vec![3.0, 3.0], Some(1.0)),
+            (vec![3.0, 7.0], Some(3.0)),
+        ]));
+        assert_eq!(rolling(&series, 2, rolled_sum()), Some(vec![4.0, 6.0, 10.0]));
+        assert_eq!(rolling(&series, 1, rolled_sum()), Some(series.to_vec()));
+        assert_eq!(rolling(&series, 2, rolled_mean_std()), Some(vec![(2.0, 1.0), (3.0, 0.0), (5.0, 2.0)]));
+    }
+
+    #[tokio::test]
+    async fn test_rolling_signals_keep_their_edges() {
+        use crate::signals::{BollingerBands, LinRegSlope, UlcerIndex, Volatility, WindowedSMA, ZScore};
+
+        // an empty series or a window too small to roll is None, a series shorter than the window no values
+        assert_eq!(WindowedSMA::new(3).calculate(&[]).await, None);
+        assert_eq!(WindowedSMA::new(1).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(WindowedSMA::new(3).calculate(&[1.0, 2.0]).await, Some(vec![]));
+        assert_eq!(Volatility::new(0).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(BollingerBands::new(3, 2.0).calculate(&[]).await, None);
+        assert_eq!(BollingerBands::new(3, 2.0).calculate(&[1.0, 2.0]).await, Some(vec![]));
+        assert_eq!(ZScore::new(0).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(LinRegSlope::new(3).calculate(&[]).await, None);
+        assert_eq!(LinRegSlope::new(1).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(LinRegSlope::new(3).calculate(&[1.0, 2.0]).await, Some(vec![]));
+        assert_eq!(UlcerIndex::new(3).calculate(&[]).await, None);
+        assert_eq!(UlcerIndex::new(3).calculate(&[1.0, 2.0]).await, Some(vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_rolling_signals_match_naive_windows() {
+        use crate::signals::{mean, std_dev, BollingerBands, Volatility, WindowedSMA};
+
+        // a long random walk around 100 so the rolling sums have plenty of chances to drift
+        let mut seed: u64 = 42;
+        let series: Vec<f64> = (0..5_000)
+            .scan(100.0, |price, _| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                *price += ((seed >> 33) as f64 / (1u64 << 31) as f64) - 0.5;
+                Some(*price)
+            })
+            .collect();
+        // rolling sums are not bit for bit identical to summing each window afresh
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+        for window in [2, 5, 20, 200] {
+            let sma = WindowedSMA::new(window).calculate(&series).await.unwrap();
+            let naive: Vec<f64> = series.windows(window).map(mean).collect();
+            assert_eq!(sma.len(), naive.len());
+            assert!(sma.iter().zip(&naive).all(|(a, b)| close(*a, *b)), "sma window {}", window);
+
+            let volatility = Volatility::new(window).calculate(&series).await.unwrap();
+            let naive: Vec<f64> = series.windows(window).map(std_dev).collect();
+            assert_eq!(volatility.len(), naive.len());
+            assert!(volatility.iter().zip(&naive).all(|(a, b)| close(*a, *b)), "volatility window {}", window);
+
+            let bands = BollingerBands::new(window, 2.0).calculate(&series).await.unwrap();
+            let naive: Vec<(f64, f64, f64)> = series
+                .windows(window)
+                .map(|w| (mean(w) - 2.0 * std_dev(w), mean(w), mean(w) + 2.0 * std_dev(w)))
+                .collect();
+            assert_eq!(bands.len(), naive.len());
+            assert!(
+                bands.iter().zip(&naive).all(|(a, b)| close(a.0, b.0) && close(a.1, b.1) && close(a.2, b.2)),
+                "bollinger window {}",
+                window
+            );
+        }
+    }
+
+    #[test]
+    fn test_resample_apply() {
+        use crate::signals::Ohlcv;
+
+        // 30 consecutive days from Monday 1st January 2024
+        let bars: Vec<Ohlcv> = (0..30)
+            .map(|i| {
+                let close = 10.0 + i as f64;
+                Ohlcv { timestamp: 1_704_067_200 + i * 86_400, open: close - 0.5, high: close + 1.0, low: close - 1.0, close, volume: 100 }
+            })
+            .collect();
+        assert_eq!(Resample::Weekly.apply(&[]), vec![]);
+
+        let weeks = Resample::Weekly.apply(&bars);
+        assert_eq!(weeks.len(), 5);
+        assert_eq!(weeks[0], Ohlcv { timestamp: 1_704_067_200, open: 9.5, high: 17.0, low: 9.0, close: 16.0, volume: 700 });
+        assert_eq!(weeks[1].timestamp, bars[7].timestamp);
+        assert_eq!(weeks[1].close, 23.0);
+        // the 29th and 30th start a week of their own
+        assert_eq!((weeks[4].close, weeks[4].volume), (39.0, 200));
+
+        let months = Resample::Monthly.apply(&bars);
+        assert_eq!(months, vec![Ohlcv { timestamp: 1_704_067_200, open: 9.5, high: 40.0, low: 9.0, close: 39.0, volume: 3000 }]);
+        assert_eq!("Monthly".parse::<Resample>(), Ok(Resample::Monthly));
+        assert!("daily".parse::<Resample>().is_err());
+    }
+
+    #[test]
+    fn test_fetch_error_from_yahoo_error() {
+        use yahoo_finance_api::YahooError;
+
+        let status = |s: &str| FetchError::from(YahooError::FetchFailed(s.to_string()));
+        assert_eq!(status("429 Too Many Requests"), FetchError::RateLimited);
+        assert_eq!(status("404 Not Found"), FetchError::NotFound);
+        assert_eq!(status("503 Service Unavailable"), FetchError::Network("503 Service Unavailable".to_string()));
+        assert_eq!(FetchError::from(YahooError::EmptyDataSet), FetchError::Empty);
+        assert!(matches!(FetchError::from(YahooError::InvalidJson), FetchError::Decode(_)));
+        let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert!(matches!(FetchError::from(YahooError::DeserializeFailed(json)), FetchError::Decode(_)));
+
+        assert!(status("429 Too Many Requests").is_transient());
+        assert!(status("502 Bad Gateway").is_transient());
+        assert!(!status("404 Not Found").is_transient());
+        assert!(!FetchError::Empty.is_transient());
+        assert!(status("502 Bad Gateway").is_retryable() && FetchError::RateLimited.is_retryable());
+        assert!(FetchError::TimedOut.is_transient() && !FetchError::TimedOut.is_retryable());
+        assert_eq!(std::io::Error::from(FetchError::TimedOut).kind(), std::io::ErrorKind::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_chaikin_money_flow_calculate() {
+        use crate::signals::{AsyncOhlcSignal, ChaikinMoneyFlow, Ohlcv};
+
+        let bar = |close: f64, volume: u64| Ohlcv { timestamp: 0, open: 10.0, high: 11.0, low: 9.0, close, volume };
+        let signal = ChaikinMoneyFlow::new(3);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(10.0, 100), bar(10.0, 100)]).await, None);
+        assert_eq!(ChaikinMoneyFlow::new(0).calculate_ohlc(&[bar(10.0, 100)]).await, None);
+
+        // closing near the highs on volume is accumulation, near the lows distribution
+        let accumulation = [bar(10.8, 100), bar(10.9, 200), bar(11.0, 100)];
+        let cmf = signal.calculate_ohlc(&accumulation).await.unwrap();
+        assert_eq!(cmf.len(), 1);
+        assert!((cmf[0] - (0.8 * 100.0 + 0.9 * 200.0 + 100.0) / 400.0).abs() < 1e-12);
+        let distribution = [bar(9.2, 100), bar(9.1, 200), bar(9.0, 100)];
+        assert!(signal.calculate_ohlc(&distribution).await.unwrap()[0] < 0.0);
+
+        let flat = Ohlcv { timestamp: 0, open: 10.0, high: 10.0, low: 10.0, close: 10.0, volume: 100 };
+        assert_eq!(signal.calculate_ohlc(&[flat; 3]).await, Some(vec![0.0]));
+        assert_eq!(signal.calculate_ohlc(&[bar(11.0, 0); 4]).await, Some(vec![0.0, 0.0]));
+    }
+
+    #[tokio::test]
+    async fn test_rsi_calculate() {
+        use crate::signals::Rsi;
+
+        let signal = Rsi::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(Rsi::new(0).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0, 3.0]).await, Some(vec![]));
+        assert_eq!(signal.calculate(&[1.0, 2.0, 3.0, 4.0, 5.0]).await, Some(vec![100.0, 100.0]));
+        assert_eq!(signal.calculate(&[5.0, 4.0, 3.0, 2.0]).await, Some(vec![0.0]));
+        assert_eq!(signal.calculate(&[5.0; 4]).await, Some(vec![50.0]));
+
+        // gains 2, 1 and a loss of 1 average to 1 and 1/3, then a loss of 2 smooths them to 2/3 and 8/9
+        let rsi = signal.calculate(&[10.0, 12.0, 13.0, 12.0, 10.0]).await.unwrap();
+        assert!((rsi[0] - 75.0).abs() < 1e-12);
+        assert!((rsi[1] - 100.0 * (2.0 / 3.0) / (2.0 / 3.0 + 8.0 / 9.0)).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_signal_spec_round_trip() {
+        use crate::signals::{BollingerBands, Ema, MaxPrice, Rsi, SignalSpec, WindowedSMA};
+
+        let specs = vec![
+            SignalSpec::Sma(WindowedSMA::new(3)),
+            SignalSpec::Ema(Ema::new(2)),
+            SignalSpec::Rsi(Rsi::new(3)),
+            SignalSpec::Bollinger(BollingerBands::new(2, 2.0)),
+            SignalSpec::MaxPrice,
+        ];
+        let json = serde_json::to_string(&specs).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"sma":{"window":3}},{"ema":2},{"rsi":3},{"bollinger":{"window":2,"multiplier":2.0}},"max_price"]"#
+        );
+        let parsed: Vec<SignalSpec> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, specs);
+        assert!(serde_json::from_str::<Vec<SignalSpec>>(r#"[{"macd":3}]"#).is_err());
+
+        // the parsed specs are the signals themselves, ready to run
+        let series = [1.0, 3.0, 3.0, 7.0];
+        for spec in parsed {
+            match spec {
+                SignalSpec::Sma(sma) => assert_eq!(sma.calculate(&series).await, Some(vec![7.0 / 3.0, 13.0 / 3.0])),
+                SignalSpec::Ema(ema) => assert_eq!(ema.calculate(&series).await, Ema::new(2).calculate(&series).await),
+                SignalSpec::Rsi(rsi) => assert_eq!(rsi.calculate(&series).await, Some(vec![100.0])),
+                SignalSpec::Bollinger(bands) => assert_eq!(bands.calculate(&series).await.unwrap()[2], (1.0, 5.0, 9.0)),
+                SignalSpec::MaxPrice => assert_eq!(MaxPrice.calculate(&series).await, Some(7.0)),
+                other => panic!("unexpected signal {:?}", other),
+            }
+        }
+    }
+
+
+    #[tokio::test]
+    async fn test_money_flow_index_calculate() {
+        use crate::signals::{AsyncOhlcSignal, MoneyFlowIndex, Ohlcv};
+
+        let bar = |close: f64, volume: u64| Ohlcv { timestamp: 0, open: close, high: close + 1.0, low: close - 1.0, close, volume };
+        let signal = MoneyFlowIndex::new(2);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(10.0, 100), bar(11.0, 100)]).await, None);
+        assert_eq!(MoneyFlowIndex::new(0).calculate_ohlc(&[bar(10.0, 100); 3]).await, None);
+
+        // only rising typical prices: no negative flow at all
+        let rising = [bar(10.0, 100), bar(11.0, 100), bar(12.0, 300), bar(13.0, 50)];
+        assert_eq!(signal.calculate_ohlc(&rising).await, Some(vec![100.0, 100.0]));
+
+        // up 11 * 100, down 10 * 300, up 12 * 200
+        let mixed = [bar(10.0, 100), bar(11.0, 100), bar(10.0, 300), bar(12.0, 200)];
+        let mfi = signal.calculate_ohlc(&mixed).await.unwrap();
+        assert_eq!(mfi.len(), 2);
+        assert!((mfi[0] - (100.0 - 100.0 / (1.0 + 1100.0 / 3000.0))).abs() < 1e-12);
+        assert!((mfi[1] - (100.0 - 100.0 / (1.0 + 2400.0 / 3000.0))).abs() < 1e-12);
+        assert!(mfi.iter().all(|value| (0.0..=100.0).contains(value)));
+
+        assert_eq!(signal.calculate_ohlc(&[bar(10.0, 100); 3]).await, Some(vec![50.0]));
+    }
+
+
+    #[tokio::test]
+    async fn test_dpo_calculate() {
+        use crate::signals::Dpo;
+
+        let signal = Dpo::new(4);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(Dpo::new(1).calculate(&[1.0, 2.0, 3.0]).await, None);
+        assert_eq!(signal.calculate(&[2.0, 4.0, 6.0, 8.0, 10.0, 12.0]).await, None);
+
+        // a window of 4 is displaced by 3: 14 less the SMA of 2..8, 9 less that of 4..10
+        let series = [2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0, 9.0];
+        assert_eq!(signal.calculate(&series).await, Some(vec![14.0 - 5.0, 9.0 - 7.0]));
+        assert_eq!(signal.calculate(&series[..7]).await, Some(vec![9.0]));
+
+        assert_eq!(Dpo::new(5).calculate(&[3.0; 10]).await, Some(vec![0.0; 3]));
+    }
+
+
+    #[tokio::test]
+    async fn test_aroon_calculate() {
+        use crate::signals::{AsyncOhlcSignal, Aroon, Ohlcv};
+
+        let signal = Aroon::new(4);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(Aroon::new(0).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0, 3.0, 4.0]).await, None);
+
+        // the high made on the fifth close goes stale a quarter at a time while the lows become fresh
+        let series = [1.0, 2.0, 3.0, 4.0, 5.0, 4.0, 3.0, 2.0];
+        assert_eq!(
+            signal.calculate(&series).await,
+            Some(vec![(100.0, 0.0), (75.0, 0.0), (50.0, 100.0), (25.0, 100.0)])
+        );
+
+        let bar = |high: f64, low: f64| Ohlcv { timestamp: 0, open: low, high, low, close: low, volume: 0 };
+        let bars = [bar(10.0, 5.0), bar(12.0, 4.0), bar(11.0, 6.0)];
+        assert_eq!(Aroon::new(2).calculate_ohlc(&bars).await, Some(vec![(50.0, 50.0)]));
+    }
+
+
+    #[tokio::test]
+    async fn test_hull_ma_calculate() {
+        use crate::signals::{HullMA, WindowedSMA};
+
+        let signal = HullMA::new(4);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(HullMA::new(1).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0, 3.0, 4.0]).await, Some(vec![]));
+        assert_eq!(signal.calculate(&[3.0; 9]).await, Some(vec![3.0; 5]));
+
+        // a step from 0 to 10: the SMA ends at the same prices one sooner, so aligned the hull ma is at [k + 1]
+        let mut series = vec![0.0; 8];
+        series.extend([10.0; 8]);
+        let hull = signal.calculate(&series).await.unwrap();
+        let sma = WindowedSMA::new(4).calculate(&series).await.unwrap();
+        assert_eq!(hull.len(), series.len() - 4 - 2 + 2);
+        assert_eq!(hull.len(), sma.len() - 1);
+        assert!((hull[4] - 56.0 / 9.0).abs() < 1e-12);
+        assert_eq!(sma[5], 2.5);
+        for k in 4..7 {
+            assert!(hull[k] > sma[k + 1], "price {}: hull {} vs sma {}", k + 4, hull[k], sma[k + 1]);
+        }
+        assert!(hull.iter().rev().take(4).all(|value| (value - 10.0).abs() < 1e-12));
+    }
+
+
+    #[tokio::test]
+    async fn test_rate_of_change_calculate() {
+        use crate::signals::RateOfChange;
+
+        let signal = RateOfChange::new(2);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(RateOfChange::new(0).calculate(&[1.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, Some(vec![]));
+        assert_eq!(signal.calculate(&[10.0, 20.0, 15.0, 0.0, 30.0, 6.0]).await, Some(vec![50.0, -100.0, 100.0, 0.0]));
+    }
+
+
+    #[tokio::test]
+    async fn test_coppock_curve_calculate() {
+        use crate::signals::CoppockCurve;
+
+        let signal = CoppockCurve::new(3, 2, 2);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0, 3.0, 4.0]).await, None);
+        assert_eq!(CoppockCurve::new(3, 2, 0).calculate(&[1.0; 10]).await, None);
+
+        // 14 prices less the 3 the longer rate of change needs and 1 more for the weighting
+        assert_eq!(CoppockCurve::new(3, 2, 2).calculate(&[5.0; 14]).await, Some(vec![0.0; 10]));
+        assert_eq!(CoppockCurve::new(14, 11, 10).calculate(&[20.0; 30]).await.map(|curve| curve.len()), Some(7));
+
+        // rates of change over 3: [50, 20], over 2: [25, 20]; sums [75, 40] weighted 1 and 2
+        let curve = signal.calculate(&[10.0, 12.0, 12.0, 15.0, 14.4]).await.unwrap();
+        assert_eq!(curve.len(), 1);
+        assert!((curve[0] - (75.0 + 2.0 * 40.0) / 3.0).abs() < 1e-9);
+    }
+
+
+    #[tokio::test]
+    async fn test_ulcer_index_calculate() {
+        use crate::signals::UlcerIndex;
+
+        let signal = UlcerIndex::new(4);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(UlcerIndex::new(1).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, Some(vec![]));
+
+        let rising = signal.calculate(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).await.unwrap();
+        assert_eq!(rising.len(), 3);
+        assert!(rising.iter().all(|value| value.abs() < 1e-12));
+
+        // a 25% drawdown on one of four days
+        assert_eq!(signal.calculate(&[10.0, 12.0, 9.0, 12.0]).await, Some(vec![12.5]));
+        let windows = UlcerIndex::new(3).calculate(&[10.0, 12.0, 9.0, 12.0]).await.unwrap();
+        assert!(windows.iter().all(|value| (value - (625.0_f64 / 3.0).sqrt()).abs() < 1e-12));
+        assert_eq!(UlcerIndex::new(2).calculate(&[0.0, 0.0, -1.0]).await, Some(vec![0.0, 0.0]));
+    }
+
+
+    #[tokio::test]
+    async fn test_twap_calculate() {
+        use crate::signals::{AsyncOhlcSignal, Ohlcv, Twap};
+
+        let bar = |timestamp: u64, price: f64| Ohlcv { timestamp, open: price, high: price + 1.0, low: price - 1.0, close: price, volume: 0 };
+        let signal = Twap::new(3);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(Twap::new(0).calculate_ohlc(&[bar(0, 1.0), bar(60, 2.0)]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(0, 1.0), bar(60, 2.0), bar(120, 3.0)]).await, None);
+
+        // the last bar stands for 8 of the window's 10 minutes, pulling the average well above the mean of 30
+        let uneven = [bar(0, 10.0), bar(60, 20.0), bar(120, 30.0), bar(600, 40.0)];
+        let twap = signal.calculate_ohlc(&uneven).await.unwrap();
+        assert_eq!(twap, vec![(20.0 * 60.0 + 30.0 * 60.0 + 40.0 * 480.0) / 600.0]);
+        assert!(twap[0] > 30.0);
+
+        let even = [bar(0, 10.0), bar(60, 20.0), bar(120, 30.0), bar(180, 40.0), bar(240, 20.0)];
+        assert_eq!(signal.calculate_ohlc(&even).await, Some(vec![30.0, 30.0]));
+        assert_eq!(signal.calculate_ohlc(&[bar(5, 10.0), bar(5, 20.0), bar(5, 30.0), bar(5, 40.0)]).await, Some(vec![30.0]));
+    }
+
+
+    #[tokio::test]
+    async fn test_rolling_beta_calculate() {
+        use crate::signals::{AsyncPairSignal, RollingBeta};
+
+        let signal = RollingBeta::new(4);
+        assert_eq!(signal.calculate_pair(&[], &[]).await, None);
+        assert_eq!(RollingBeta::new(1).calculate_pair(&[0.1, 0.2], &[0.1, 0.2]).await, None);
+        assert_eq!(signal.calculate_pair(&[0.1, 0.2, 0.3], &[0.1, 0.2]).await, None);
+        assert_eq!(signal.calculate_pair(&[0.1, 0.2], &[0.1, 0.2]).await, Some(vec![]));
+
+        // the returns follow the benchmark for 8 days, then move twice as far
+        let benchmark: Vec<f64> = (0..16).map(|day| [0.01, -0.02, 0.015, -0.005][day % 4]).collect();
+        let returns: Vec<f64> = benchmark.iter().enumerate().map(|(day, r)| if day < 8 { *r } else { 2.0 * r }).collect();
+        let beta = signal.calculate_pair(&returns, &benchmark).await.unwrap();
+        assert_eq!(beta.len(), 13);
+        // windows ending before day 8 are all 1, those starting from it all 2, and those across it in between
+        assert!(beta[..5].iter().all(|beta| (beta - 1.0).abs() < 1e-12));
+        assert!(beta[5..8].iter().all(|beta| *beta > 1.0 && *beta < 2.0 + 1e-12));
+        assert!(beta[8..].iter().all(|beta| (beta - 2.0).abs() < 1e-12));
+        assert_eq!(signal.calculate_pair(&[0.1, 0.2, 0.3, 0.4], &[0.01; 4]).await, Some(vec![0.0]));
+    }
+
+
+    #[tokio::test]
+    async fn test_weighted_ma_calculate() {
+        use crate::signals::{WeightedMA, WindowedSMA};
+
+        let signal = WeightedMA::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(WeightedMA::new(1).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, Some(vec![]));
+        assert_eq!(signal.calculate(&[1.0, 2.0, 3.0, 6.0]).await, Some(vec![14.0 / 6.0, 26.0 / 6.0]));
+
+        // after a jump the weighted average sits closer to the latest prices than the simple one
+        let series = [10.0, 10.0, 10.0, 10.0, 20.0, 20.0];
+        let wma = signal.calculate(&series).await.unwrap();
+        let sma = WindowedSMA::new(3).calculate(&series).await.unwrap();
+        assert_eq!(wma.len(), sma.len());
+        assert!(wma[2] > sma[2] && wma[3] > sma[3]);
+        assert_eq!(wma[0], sma[0]);
+    }
+
+
+    #[tokio::test]
+    async fn test_std_error_bands_calculate() {
+        use crate::signals::StdErrorBands;
+
+        let signal = StdErrorBands::new(3, 2.0);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(StdErrorBands::new(1, 2.0).calculate(&[1.0, 2.0]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0]).await, Some(vec![]));
+
+        // points on a line have no error, so all three bands are the line at the latest price
+        let linear: Vec<f64> = (0..6).map(|x| 5.0 + 1.5 * x as f64).collect();
+        let bands = StdErrorBands::new(4, 2.0).calculate(&linear).await.unwrap();
+        assert_eq!(bands.len(), 3);
+        for (band, price) in bands.iter().zip(&linear[3..]) {
+            assert!((band.0 - price).abs() < 1e-9 && (band.1 - price).abs() < 1e-9 && (band.2 - price).abs() < 1e-9);
+        }
+
+        // the line through 1, 3, 2 is 1.5, 2, 2.5 with residuals -0.5, 1, -0.5
+        let bands = signal.calculate(&[1.0, 3.0, 2.0]).await.unwrap();
+        let error = 1.5_f64.sqrt();
+        assert!((bands[0].1 - 2.5).abs() < 1e-12);
+        assert!((bands[0].0 - (2.5 - 2.0 * error)).abs() < 1e-12 && (bands[0].2 - (2.5 + 2.0 * error)).abs() < 1e-12);
+        assert_eq!(StdErrorBands::new(2, 2.0).calculate(&[1.0, 3.0]).await, Some(vec![(3.0, 3.0, 3.0)]));
+    }
+
+
+    #[tokio::test]
+    async fn test_schaff_trend_cycle_calculate() {
+        use crate::signals::SchaffTrendCycle;
+
+        let signal = SchaffTrendCycle::new(3, 6, 4);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(SchaffTrendCycle::new(3, 6, 0).calculate(&[1.0; 20]).await, None);
+        // the slow EMA and two cycles need 6 + 2 * 3 points
+        let trending: Vec<f64> = (0..40).map(|x| 100.0 + x as f64 + 3.0 * (x as f64 / 3.0).sin()).collect();
+        assert_eq!(signal.calculate(&trending[..11]).await, None);
+        assert_eq!(signal.calculate(&trending[..12]).await.map(|cycle| cycle.len()), Some(1));
+
+        let cycle = signal.calculate(&trending).await.unwrap();
+        assert_eq!(cycle.len(), 40 - (6 + 2 * 3) + 1);
+        assert!(cycle.iter().all(|value| (0.0..=100.0).contains(value)));
+        // a flat MACD has no range to sit in
+        assert_eq!(signal.calculate(&[7.0; 14]).await, Some(vec![0.0; 3]));
+    }
+
+
+    #[tokio::test]
+    async fn test_force_index_calculate() {
+        use crate::signals::{AsyncOhlcSignal, ForceIndex, Ohlcv};
+
+        let bar = |close: f64, volume: u64| Ohlcv { timestamp: 0, open: close, high: close, low: close, close, volume };
+        let signal = ForceIndex::new(2);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(10.0, 100)]).await, None);
+        assert_eq!(ForceIndex::new(0).calculate_ohlc(&[bar(10.0, 100); 3]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(10.0, 100), bar(11.0, 100)]).await, Some(vec![]));
+
+        // up a dollar a day on 100 shares, then down two a day on 300
+        let bars = [bar(10.0, 100), bar(11.0, 100), bar(12.0, 100), bar(13.0, 100), bar(11.0, 300), bar(9.0, 300), bar(7.0, 300)];
+        let force = signal.calculate_ohlc(&bars).await.unwrap();
+        assert_eq!(force.len(), 5);
+        assert_eq!(force[..2], [100.0, 100.0]);
+        // the first fall outweighs the rise already averaged in, and the selling keeps it negative
+        assert!((force[2] - (100.0 + 2.0 / 3.0 * (-600.0 - 100.0))).abs() < 1e-9);
+        assert!(force[2..].iter().all(|value| *value < 0.0));
+    }
+
+    #[test]
+    fn test_session_quotes() {
+        use yahoo_finance_api::YResponse;
+
+        let session = |start: u32, end: u32| serde_json::json!({ "timezone": "EDT", "start": start, "end": end, "gmtoffset": -14400 });
+        // 9:30 to 16:00 New York on Monday 3rd June 2024, with a bar before the open and one after the close
+        let (open, close) = (1_717_421_400_u32, 1_717_444_800_u32);
+        let timestamps = [open - 3_600, open, open + 3_600, close];
+        let response = |trading_periods: serde_json::Value| {
+            YResponse::from_json(serde_json::json!({ "chart": { "error": null, "result": [{
+                "meta": {
+                    "currency": "USD", "symbol": "AAPL", "exchangeName": "NMS", "instrumentType": "EQUITY",
+                    "regularMarketTime": close, "gmtoffset": -14400, "timezone": "EDT",
+                    "exchangeTimezoneName": "America/New_York", "regularMarketPrice": 13.0, "chartPreviousClose": 9.0,
+                    "priceHint": 2, "dataGranularity": "1h", "range": "", "validRanges": ["1d"],
+                    "currentTradingPeriod": {
+                        "pre": session(open - 19_800, open), "regular": session(open, close), "post": session(close, close + 14_400),
+                    },
+                    "tradingPeriods": trading_periods,
+                },
+                "timestamp": timestamps,
+                "indicators": { "quote": [{
+                    "open": [10.0, 11.0, 12.0, 13.0], "high": [10.0, 11.0, 12.0, 13.0], "low": [10.0, 11.0, 12.0, 13.0],
+                    "close": [10.0, 11.0, 12.0, 13.0], "volume": [100, 200, 300, 400],
+                }]},
+            }]}}))
+            .unwrap()
+        };
+        let closes = |quotes: Vec<Quote>| quotes.iter().map(|quote| quote.close).collect::<Vec<f64>>();
+
+        let marked = response(serde_json::json!({
+            "pre": [[session(open - 19_800, open)]], "regular": [[session(open, close)]], "post": [[session(close, close + 14_400)]],
+        }));
+        assert_eq!(closes(provider::session_quotes(&marked, true).unwrap()), vec![10.0, 11.0, 12.0, 13.0]);
+        // the bar at the close is the first of the post market
+        assert_eq!(closes(provider::session_quotes(&marked, false).unwrap()), vec![11.0, 12.0]);
+        // sessions listed as a bare array are the regular ones
+        let regular_only = response(serde_json::json!([[session(open, close)]]));
+        assert_eq!(closes(provider::session_quotes(&regular_only, false).unwrap()), vec![11.0, 12.0]);
+        let unmarked = response(serde_json::json!({}));
+        assert_eq!(closes(provider::session_quotes(&unmarked, false).unwrap()), vec![10.0, 11.0, 12.0, 13.0]);
+    }
+
+
+    #[tokio::test]
+    async fn test_min_max_calculate() {
+        use crate::signals::{MaxPrice, MinMax, MinPrice};
+
+        let signal = MinMax;
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[f64::NAN]).await, None);
+        let inputs: [&[f64]; 6] = [
+            &[1.0],
+            &[1.0, 0.0],
+            &[2.0, 3.0, 5.0, 6.0, 1.0, 2.0, 10.0],
+            &[0.0, 3.0, 5.0, 6.0, 1.0, 2.0, 1.0],
+            &[-4.0, -1.5, -9.0],
+            &[3.0, f64::NAN, 7.0, 2.0],
+        ];
+        for series in inputs {
+            let expected = (MinPrice.calculate(series).await.unwrap(), MaxPrice.calculate(series).await.unwrap());
+            assert_eq!(signal.calculate(series).await, Some(expected));
+        }
+        assert_eq!(signal.calculate(&[f64::NAN, 4.0, f64::NAN]).await, Some((4.0, 4.0)));
+    }
+
+
+    #[tokio::test]
+    async fn test_elder_ray_calculate() {
+        use crate::signals::{AsyncOhlcSignal, ElderRay, Ohlcv};
+
+        let bar = |close: f64| Ohlcv { timestamp: 0, open: close, high: close + 0.5, low: close - 0.5, close, volume: 100 };
+        let signal = ElderRay::new(3);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(10.0), bar(11.0)]).await, None);
+        assert_eq!(ElderRay::new(0).calculate_ohlc(&[bar(10.0); 3]).await, None);
+        // the EMA is the average of the first three closes, 11
+        assert_eq!(signal.calculate_ohlc(&[bar(10.0), bar(11.0), bar(12.0)]).await, Some(vec![(1.5, 0.5)]));
+
+        // up two a bar, then down two a bar
+        let closes = [10.0, 12.0, 14.0, 16.0, 18.0, 20.0, 18.0, 16.0, 14.0, 12.0, 10.0];
+        let bars: Vec<Ohlcv> = closes.iter().map(|close| bar(*close)).collect();
+        let ray = signal.calculate_ohlc(&bars).await.unwrap();
+        assert_eq!(ray.len(), closes.len() - 2);
+        // the closes run ahead of the lagging average in the rise, then fall below it
+        assert!(ray[..4].iter().all(|(bull, bear)| *bull > 0.0 && *bear > 0.0));
+        assert!(ray[6..].iter().all(|(bull, bear)| *bull < 0.0 && *bear < 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_kama_calculate() {
+        use crate::signals::Kama;
+
+        let signal = Kama::new(10, 2, 30);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0; 10]).await, None);
+        assert_eq!(Kama::new(0, 2, 30).calculate(&[1.0; 20]).await, None);
+        assert_eq!(signal.calculate(&[1.0; 11]).await, Some(vec![1.0; 2]));
+
+        // a steady trend is perfectly efficient, so the average moves at the fast EMA's pace and stays close behind
+        let trending: Vec<f64> = (0..40).map(|x| 100.0 + 2.0 * x as f64).collect();
+        let kama = signal.calculate(&trending).await.unwrap();
+        assert_eq!(kama.len(), 40 - 10 + 1);
+        assert!((kama.last().unwrap() - trending.last().unwrap()).abs() < 2.0 * 2.0);
+
+        // alternating noise around 100 makes no net progress, so the average barely moves from its seed
+        let noisy: Vec<f64> = (0..40).map(|x| if x % 2 == 0 { 95.0 } else { 105.0 }).collect();
+        let kama = signal.calculate(&noisy).await.unwrap();
+        assert_eq!(kama[0], 105.0);
+        assert!(kama.iter().all(|value| (95.0..=105.0).contains(value)));
+        assert!(kama.windows(2).all(|pair| (pair[1] - pair[0]).abs() < 0.1));
+    }
+
+    #[tokio::test]
+    async fn test_vortex_calculate() {
+        use crate::signals::{AsyncOhlcSignal, Ohlcv, Vortex};
+
+        let bar = |close: f64| Ohlcv { timestamp: 0, open: close, high: close + 1.0, low: close - 1.0, close, volume: 100 };
+        let signal = Vortex::new(3);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(signal.calculate_ohlc(&[bar(10.0); 3]).await, None);
+        assert_eq!(Vortex::new(0).calculate_ohlc(&[bar(10.0); 5]).await, None);
+        let flat = Ohlcv { timestamp: 0, open: 5.0, high: 5.0, low: 5.0, close: 5.0, volume: 0 };
+        assert_eq!(signal.calculate_ohlc(&[flat; 4]).await, Some(vec![(0.0, 0.0)]));
+
+        let up: Vec<Ohlcv> = (0..10).map(|x| bar(10.0 + x as f64)).collect();
+        let vortex = signal.calculate_ohlc(&up).await.unwrap();
+        assert_eq!(vortex.len(), 10 - 3);
+        // each high is 3 over the previous low and each low 1 under the previous high, in a true range of 2
+        assert!(vortex.iter().all(|(plus, minus)| (plus - 1.5).abs() < 1e-12 && (minus - 0.5).abs() < 1e-12));
+
+        let down: Vec<Ohlcv> = up.iter().rev().copied().collect();
+        let vortex = signal.calculate_ohlc(&down).await.unwrap();
+        assert!(vortex.iter().all(|(plus, minus)| minus > plus));
+    }
+
+    #[tokio::test]
+    async fn test_ppo_calculate() {
+        use crate::signals::{Ema, Ppo};
+
+        let signal = Ppo::new(3, 6, 4);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(Ppo::new(6, 6, 4).calculate(&[1.0; 20]).await, None);
+        assert_eq!(Ppo::new(6, 3, 4).calculate(&[1.0; 20]).await, None);
+        assert_eq!(Ppo::new(3, 6, 0).calculate(&[1.0; 20]).await, None);
+        // the slow EMA and the signal need 6 + 4 - 1 points
+        assert_eq!(signal.calculate(&[1.0; 8]).await, None);
+        assert_eq!(signal.calculate(&[1.0; 9]).await, Some((vec![0.0], vec![0.0], vec![0.0])));
+
+        let series: Vec<f64> = (0..30).map(|x| 50.0 + x as f64 + 4.0 * (x as f64 / 2.0).sin()).collect();
+        let (line, signal_line, histogram) = signal.calculate(&series).await.unwrap();
+        assert_eq!((line.len(), signal_line.len(), histogram.len()), (30 - 9 + 1, 22, 22));
+        assert!(line.iter().zip(&signal_line).zip(&histogram).all(|((line, signal), histogram)| (line - signal - histogram).abs() < 1e-12));
+
+        // a percentage of the slow EMA, so scaling the prices leaves it as it is
+        let fast = Ema::new(3).calculate(&series).await.unwrap();
+        let slow = Ema::new(6).calculate(&series).await.unwrap();
+        let last = 100.0 * (fast.last().unwrap() - slow.last().unwrap()) / slow.last().unwrap();
+        assert!((line.last().unwrap() - last).abs() < 1e-12);
+        let scaled: Vec<f64> = series.iter().map(|price| price * 1000.0).collect();
+        let (scaled_line, _, _) = signal.calculate(&scaled).await.unwrap();
+        assert!(scaled_line.iter().zip(&line).all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+
+    #[tokio::test]
+    async fn test_mass_index_calculate() {
+        use crate::signals::{AsyncOhlcSignal, MassIndex, Ohlcv};
+
+        let bar = |range: f64| Ohlcv { timestamp: 0, open: 100.0, high: 100.0 + range / 2.0, low: 100.0 - range / 2.0, close: 100.0, volume: 100 };
+        let signal = MassIndex::new(9, 25);
+        assert_eq!(signal.calculate_ohlc(&[]).await, None);
+        assert_eq!(MassIndex::new(0, 25).calculate_ohlc(&[bar(1.0); 60]).await, None);
+        assert_eq!(MassIndex::new(9, 0).calculate_ohlc(&[bar(1.0); 60]).await, None);
+        // two EMAs of 9 and a sum of 25 need 9 + 8 + 24 bars
+        assert_eq!(signal.calculate_ohlc(&[bar(1.0); 40]).await, None);
+        // steady ranges make every ratio 1, as do bars without a range
+        assert_eq!(signal.calculate_ohlc(&[bar(2.0); 41]).await, Some(vec![25.0]));
+        assert_eq!(signal.calculate_ohlc(&[bar(0.0); 42]).await, Some(vec![25.0; 2]));
+
+        // the range steady for a while and then widening faster and faster
+        let bars: Vec<Ohlcv> = (0..80).map(|x| bar(if x < 40 { 2.0 } else { 2.0 * 1.08f64.powi(x - 40) })).collect();
+        let mass = signal.calculate_ohlc(&bars).await.unwrap();
+        assert_eq!(mass.len(), 80 - 41 + 1);
+        assert!((mass[0] - 25.0).abs() < 1e-9);
+        assert!(mass.windows(2).skip(1).all(|pair| pair[1] > pair[0]));
+        assert!(*mass.last().unwrap() > 27.0);
+    }
+
+    #[tokio::test]
+    async fn test_cmo_calculate() {
+        use crate::signals::Cmo;
+
+        let signal = Cmo::new(3);
+        assert_eq!(signal.calculate(&[]).await, None);
+        assert_eq!(signal.calculate(&[1.0, 2.0, 3.0]).await, None);
+        assert_eq!(Cmo::new(0).calculate(&[1.0, 2.0, 3.0]).await, None);
+        assert_eq!(signal.calculate(&[5.0; 6]).await, Some(vec![0.0; 3]));
+
+        let rising: Vec<f64> = (0..10).map(|x| 10.0 + x as f64 * 1.5).collect();
+        assert_eq!(signal.calculate(&rising).await, Some(vec![100.0; 7]));
+        let falling: Vec<f64> = rising.iter().rev().copied().collect();
+        assert_eq!(signal.calculate(&falling).await, Some(vec![-100.0; 7]));
+
+        // rises of 2 and 1 against a fall of 1
+        let cmo = signal.calculate(&[10.0, 12.0, 11.0, 12.0]).await.unwrap();
+        assert!((cmo[0] - 50.0).abs() < 1e-12);
+    }
+}
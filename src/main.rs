@@ -1,369 +1,3548 @@
 #![allow(dead_code)]
-mod signals;
+mod alert;
+mod config;
+mod correlation;
+mod metadata;
+mod portfolio;
+mod report;
+mod throttle;
 
 //--------------------------------------------------------------------------------------------------
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::io::{IsTerminal, Write};
+use std::str::FromStr;
+use std::sync::Arc;
 use async_recursion::async_recursion;
-use time::OffsetDateTime;
 use tokio::{task, fs};
+use tokio::task::JoinSet;
 use tokio::time as tokio_time;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter, Error, ErrorKind};
+use tokio::io::{AsyncReadExt, Error};
 use tokio_stream::{StreamExt, wrappers::IntervalStream};
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use chrono::prelude::*;
 use chrono::TimeDelta;
-use yahoo_finance_api as yahoo;
-use signals::AsyncStockSignal;
-use signals::{
-    PriceDifference,
-    WindowedSMA,
-    MaxPrice,
-    MinPrice
+use chrono_tz::Tz;
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::{Rng, RngExt};
+use async_streams::interval::TRADING_DAYS_PER_YEAR;
+use async_streams::{
+    StockRow,
+    Column,
+    OutputSink,
+    CsvSink,
+    JsonSink,
+    TableSink,
+    ParquetSink,
+    SqliteSink,
+    QuoteProvider,
+    YahooProvider,
+    CsvProvider,
+    Quote,
+    FetchError,
+    FetchTelemetry,
+    Ohlcv,
+    Resample,
+    Interval,
+    PriceSource,
+    SignalSettings,
+    calculate_signals,
+    collect_rows,
+    report_fetch_error,
 };
+use alert::Alert;
+use config::Config;
+use correlation::CorrelationMatrix;
+use portfolio::Portfolio;
+use metadata::{CurrencyConversion, RunMetadata, SignalWindows};
+use report::{Report, ReportParameters, SymbolReport};
+use throttle::Throttle;
 //--------------------------------------------------------------------------------------------------
 #[derive(Parser, Debug)]
 #[clap(
     version = "2.0",
     author = "Serle Shuman",
-    about = "Async Rust project"
+    about = "Async Rust project",
+    after_help = "Exit codes: 0 success, 1 invalid arguments, config or output, 2 an --alert was breached, 3 every symbol \
+                  failed, 4 some symbols failed, 5 --validate-output found a file that doesn't hold the rows written"
 )]
 struct Opts {
     #[clap(short, long)]
     symbols: Option<String>,
+    /// Start of the range, defaulting to a lookback suited to the --interval and --resample period, e.g. 2 weeks for
+    /// daily bars and a year for weekly ones
     #[clap(short, long)]
     from: Option<String>,
+    /// End of the range, defaulting to now. An end equal to the start is widened by a day to cover that day's quotes
     #[clap(short, long)]
     to: Option<String>,
+    /// TOML file of default settings; flags given on the command line take precedence
+    #[clap(long, value_name = "PATH", value_parser = Config::load)]
+    config: Option<Config>,
+    /// Number of closes averaged for the sma column
+    #[clap(long)]
+    sma_window: Option<usize>,
+    /// Seconds to wait for each symbol's data before skipping it
+    #[clap(long, default_value_t = 30)]
+    timeout: u64,
+    /// Wait a random 0 to MILLIS milliseconds before each request, to spread out the concurrent fetches
+    #[clap(long, value_name = "MILLIS", default_value_t = 0)]
+    jitter: u64,
+    /// Suppress everything but errors on stdout; the sinks are still written
+    #[clap(short, long)]
+    quiet: bool,
+    /// Write rows to PATH in FORMAT (csv, json, table, parquet or report); repeat for several destinations. A PATH of `-` is
+    /// stdout and replaces the usual echo. Defaults to data.<output-format>. Each file gets a PATH.meta.json beside it, recording
+    /// the parameters of the run
+    #[clap(long = "sink", value_name = "PATH:FORMAT")]
+    sinks: Vec<SinkSpec>,
+    /// Also upsert rows into the signals table of this SQLite database
+    #[clap(long, value_name = "PATH")]
+    sqlite: Option<String>,
+    /// Format of the default output file when no --sink is given (csv, json, table, parquet or report). A table is printed
+    /// to stdout instead. A report is a single JSON document of each symbol's full sma, ema and rsi series
+    #[clap(long, default_value = "csv")]
+    output_format: OutputFormat,
+    /// Comma separated columns to calculate and write, in order, e.g. symbol,price,sma
+    #[clap(long, value_delimiter = ',')]
+    columns: Vec<Column>,
+    /// Add an annualized volatility column
+    #[clap(long)]
+    annualized_vol: bool,
+    /// Add a rel_strength column comparing each symbol to SYMBOL, e.g. SPY: its growth over the benchmark's across the
+    /// days both traded, (1 + return) / (1 + benchmark return), so above 1 outperformed. Needs a daily or longer
+    /// --interval
+    #[clap(long, value_name = "SYMBOL")]
+    rs_benchmark: Option<String>,
+    /// Write each file sink as one file per symbol with its own header, e.g. data_MSFT.csv and data_AAPL.csv in place of
+    /// data.csv. Reports and stdout are still written whole
+    #[clap(long)]
+    split_by_symbol: bool,
+    /// Read each CSV file back once written and check it holds every row written, with only finite numbers
+    #[clap(long)]
+    validate_output: bool,
+    /// Decimal places of the prices and percentages in CSV and table output
+    #[clap(long, default_value_t = StockRow::DEFAULT_PRECISION)]
+    precision: usize,
+    /// Bytes buffered for each output file between writes, at least 1024; larger buffers suit runs writing many rows
+    #[clap(long, value_name = "BYTES", default_value_t = DEFAULT_BUFFER_SIZE, value_parser = parse_buffer_size)]
+    buffer_size: usize,
+    /// Write rows in alphabetical symbol order rather than the order supplied
+    #[clap(long)]
+    sort: bool,
+    /// Repeat the previous close for trading days missing from a response instead of only warning about them
+    #[clap(long)]
+    fill_gaps: bool,
+    /// Calculate on the split and dividend adjusted closes; pass false for the raw closes, e.g. for intraday data
+    #[clap(long, default_value_t = true, action = clap::ArgAction::Set, value_name = "BOOL")]
+    use_adjusted: bool,
+    /// The price of each bar the signals are calculated on: close, adjclose, typical or hlc3 for (high + low + close)
+    /// / 3, or ohlc4 for (open + high + low + close) / 4. Defaults to adjclose, or close with --use-adjusted false
+    #[clap(long, value_name = "SOURCE", conflicts_with = "use_adjusted")]
+    price_source: Option<PriceSource>,
+    /// What to do with a symbol with a zero, negative or missing close: error to fail it, skip-symbol to leave it out
+    /// like one without data, or drop-points to calculate without those quotes
+    #[clap(long, value_name = "POLICY", default_value = "drop-points")]
+    on_bad_data: BadDataPolicy,
+    /// Convert prices from dollars into this currency, e.g. EUR, at the latest USD<CURRENCY>=X rate of the period. CSV
+    /// and table prices are followed by the currency, and the rate is recorded in the .meta.json sidecar
+    #[clap(long)]
+    currency: Option<String>,
+    /// IANA timezone, e.g. America/New_York, to label the period start in rather than UTC
+    #[clap(long, value_name = "ZONE", value_parser = parse_timezone)]
+    timezone: Option<Tz>,
+    /// Spacing of the bars fetched from Yahoo: 1m, 5m, 15m, 30m, 1h, 1d, 1wk or 1mo
+    #[clap(long, default_value = "1d")]
+    interval: Interval,
+    /// Include the pre and post market bars of an intraday --interval rather than only the regular session's. Has no
+    /// effect on daily and longer bars
+    #[clap(long)]
+    include_prepost: bool,
+    /// Aggregate the daily bars into weekly or monthly bars before calculating the signals
+    #[clap(long, value_name = "PERIOD")]
+    resample: Option<Resample>,
+    /// Only calculate on the most recent N closes of each symbol, e.g. for quick runs over long ranges. Signals with a
+    /// window longer than N have nothing to calculate
+    #[clap(long, value_name = "N")]
+    limit_rows: Option<usize>,
+    /// Rebase each symbol's prices to 100 at its first close, to compare symbols trading at very different prices
+    #[clap(long)]
+    normalize: bool,
+    /// Only fetch quotes after the last quote already in the outputs, appending a row per symbol; adds the last_quote
+    /// column the next run picks up from
+    #[clap(long)]
+    incremental: bool,
+    /// Add the time of the run to the name of each output file, e.g. data_20241014T093000.125Z.csv, so that successive
+    /// runs don't overwrite each other
+    #[clap(long, conflicts_with = "incremental")]
+    output_append_timestamp: bool,
+    /// Fetch and calculate everything but write no rows, only a summary of the run to stdout
+    #[clap(long)]
+    summary_only: bool,
+    /// Report symbols whose signal breaches a bound such as 'rsi>70' and exit with code 2; repeat to alert on any of
+    /// several. Alerted signals are calculated and written even when not among --columns
+    #[clap(long = "alert", value_name = "SIGNAL>THRESHOLD")]
+    alerts: Vec<Alert>,
+    /// Stop at the first symbol which can't be fetched, writing nothing and exiting with code 3, instead of writing the
+    /// others
+    #[clap(long)]
+    fail_fast: bool,
+    /// Count symbols without any data in the range, or with a gap longer than --max-gap-days, as failed rather than
+    /// only warning about them
+    #[clap(long)]
+    strict: bool,
+    /// Warn about symbols with more than N calendar days between consecutive bars, e.g. a delisting, which windowed
+    /// signals would span as if it weren't there; --strict skips them
+    #[clap(long, value_name = "N")]
+    max_gap_days: Option<i64>,
+    /// Read each symbol's quotes from a local CSV instead of Yahoo, at this path with {symbol} replaced, e.g.
+    /// 'data/{symbol}.csv'. The header names the columns: date and close, optionally open, high, low, adjclose and volume
+    #[clap(long, value_name = "TEMPLATE")]
+    input_csv: Option<String>,
+    /// Print only the first N rows to stdout, as a table, instead of echoing them all
+    #[clap(long, value_name = "N", conflicts_with = "tail")]
+    head: Option<usize>,
+    /// Print only the last N rows to stdout, as a table, instead of echoing them all
+    #[clap(long, value_name = "N")]
+    tail: Option<usize>,
+    /// Write the correlation matrix of the symbols' daily returns to PATH, as JSON for a .json file and CSV otherwise,
+    /// instead of the rows. Needs a daily or longer --interval
+    #[clap(long, value_name = "PATH")]
+    corr_matrix: Option<String>,
+    /// Write the equity curve of an equal weight of each symbol, over the days they all traded, to PATH, as JSON for a
+    /// .json file and CSV otherwise, instead of the rows. Needs a daily or longer --interval
+    #[clap(long, value_name = "PATH", conflicts_with = "corr_matrix")]
+    portfolio: Option<String>,
+    /// Continuously poll the S&P 500 every 30 seconds instead of writing a one-off report
+    #[clap(long)]
+    watch: bool,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand, Debug, Clone, PartialEq)]
+enum Command {
+    /// Write a commented sample config with every key at its default, for --config
+    ConfigInit {
+        #[clap(default_value = "async_streams.toml")]
+        path: String,
+        /// Overwrite the file if it already exists
+        #[clap(long)]
+        force: bool,
+    },
+}
+
+impl Command {
+    fn run(&self) -> std::io::Result<()> {
+        match self {
+            Self::ConfigInit { path, force } => {
+                default_config().init(path, *force).map_err(Error::other)?;
+                eprintln!("wrote a sample config to {}", path);
+                Ok(())
+            }
+        }
+    }
+}
+
+const DEFAULT_SYMBOLS: [&str; 4] = ["AAPL", "MSFT", "UBER", "GOOG"];
+
+///
+/// The settings used where neither the config nor the command line give one, as a config.
+///
+fn default_config() -> Config {
+    let defaults = SignalSettings::default();
+    Config {
+        symbols: Some(DEFAULT_SYMBOLS.iter().map(|symbol| symbol.to_string()).collect()),
+        sma_window: Some(defaults.sma_window),
+        risk_free_rate: Some(defaults.risk_free_rate),
+        trading_days_per_year: Some(defaults.trading_days_per_year),
+    }
 }
 //--------------------------------------------------------------------------------------------------
 
-type Signals = (String, String, f64, f64, f64, f64, f64);
+///
+/// The smallest `--buffer-size`, below which the writes to the files only get more frequent.
+///
+const MIN_BUFFER_SIZE: usize = 1024;
+
+///
+/// The capacity of each output file's buffer unless asked otherwise, that of `BufWriter::new`.
+///
+const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+fn parse_buffer_size(bytes: &str) -> Result<usize, String> {
+    match bytes.trim().parse::<usize>() {
+        Ok(bytes) if bytes >= MIN_BUFFER_SIZE => Ok(bytes),
+        Ok(bytes) => Err(format!("a buffer of {} bytes is too small, at least {} are needed", bytes, MIN_BUFFER_SIZE)),
+        Err(e) => Err(format!("'{}' isn't a number of bytes: {}", bytes.trim(), e)),
+    }
+}
+
+///
+/// A file writer buffering `capacity` bytes between writes to the file.
+///
+fn buffered(file: std::fs::File, capacity: usize) -> std::io::BufWriter<std::fs::File> {
+    std::io::BufWriter::with_capacity(capacity, file)
+}
+
+fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.trim().parse().map_err(|_| format!("unknown timezone '{}', expected an IANA name such as America/New_York", name.trim()))
+}
+
+///
+/// The symbols with repeats dropped, keeping the first spelling of each in the order first seen. Symbols differing only
+/// in case are the same ticker.
+///
+fn deduplicated(symbols: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    symbols.into_iter().filter(|symbol| seen.insert(symbol.to_uppercase())).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Csv,
+    Json,
+    Table,
+    Parquet,
+    Report,
+}
+
+impl OutputFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Table => "txt",
+            Self::Parquet => "parquet",
+            Self::Report => "json",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            "table" => Ok(Self::Table),
+            "parquet" => Ok(Self::Parquet),
+            "report" => Ok(Self::Report),
+            other => Err(format!("unknown output format '{}', expected one of: csv, json, table, parquet, report", other)),
+        }
+    }
+}
+
+///
+/// What to do with a symbol whose closes aren't all positive and finite, which the returns and ratios of the signals
+/// can't be calculated on.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum BadDataPolicy {
+    /// Fail the symbol.
+    Error,
+    /// Leave the symbol out, like one without any quotes in range.
+    SkipSymbol,
+    /// Drop the bad quotes and calculate on the rest.
+    #[default]
+    DropPoints,
+}
+
+impl FromStr for BadDataPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "skip-symbol" => Ok(Self::SkipSymbol),
+            "drop-points" => Ok(Self::DropPoints),
+            other => Err(format!("unknown bad data policy '{}', expected one of: error, skip-symbol, drop-points", other)),
+        }
+    }
+}
+
+///
+/// Where and how to write the rows, given on the command line as `PATH:FORMAT`.
+///
+#[derive(Debug, Clone, PartialEq)]
+struct SinkSpec {
+    path: String,
+    format: OutputFormat,
+    /// The only symbol whose rows are written, when splitting the output by symbol.
+    symbol: Option<String>,
+}
+
+impl SinkSpec {
+    fn is_stdout(&self) -> bool {
+        self.path == "-"
+    }
+
+    fn is_report(&self) -> bool {
+        self.format == OutputFormat::Report
+    }
+
+    ///
+    /// The file of `symbol`'s rows alone, named after this one: `out/data_MSFT.csv` for `out/data.csv`.
+    ///
+    fn for_symbol(&self, symbol: &str) -> Self {
+        Self { path: self.suffixed(symbol), format: self.format, symbol: Some(symbol.to_string()) }
+    }
+
+    ///
+    /// The file named after this one with the time of the run, e.g. `data_20241014T093000.125Z.csv` for `data.csv`, so
+    /// that each run writes a file of its own.
+    ///
+    fn timestamped(&self, at: &DateTime<Utc>) -> Self {
+        Self { path: self.suffixed(&at.format("%Y%m%dT%H%M%S%.3fZ").to_string()), ..self.clone() }
+    }
+
+    ///
+    /// The path with `_suffix` added to the file name, before any extension.
+    ///
+    fn suffixed(&self, suffix: &str) -> String {
+        let path = std::path::Path::new(&self.path);
+        let name = match (path.file_stem(), path.extension()) {
+            (Some(stem), Some(extension)) => format!("{}_{}.{}", stem.to_string_lossy(), suffix, extension.to_string_lossy()),
+            _ => format!("{}_{}", path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default(), suffix),
+        };
+        path.with_file_name(name).to_string_lossy().to_string()
+    }
+
+    ///
+    /// Open the sink, after any rows already in the file when appending, buffering `buffer_size` bytes of a file.
+    ///
+    fn open(&self, columns: &[Column], precision: usize, append: bool, buffer_size: usize) -> std::io::Result<Box<dyn OutputSink>> {
+        let append = append && !self.is_stdout();
+        if append && self.format == OutputFormat::Parquet {
+            return Err(Error::other(format!("{}: parquet output can't be appended to", self.path)));
+        }
+        if self.format == OutputFormat::Report {
+            return Err(Error::other(format!("{}: a report is written whole, not row by row", self.path)));
+        }
+        let existing = append && std::fs::metadata(&self.path).map(|m| m.len() > 0).unwrap_or(false);
+        let writer: Box<dyn Write + Send> = if self.is_stdout() {
+            Box::new(std::io::stdout())
+        } else if append {
+            Box::new(buffered(std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?, buffer_size))
+        } else {
+            Box::new(buffered(std::fs::File::create(&self.path)?, buffer_size))
+        };
+        let sink: Box<dyn OutputSink> = match self.format {
+            OutputFormat::Csv if existing => Box::new(CsvSink::with_columns(writer, columns.to_vec()).with_precision(precision).without_header()),
+            OutputFormat::Csv => Box::new(CsvSink::with_columns(writer, columns.to_vec()).with_precision(precision)),
+            OutputFormat::Json => Box::new(JsonSink::with_columns(writer, columns.to_vec())),
+            OutputFormat::Table => Box::new(TableSink::with_columns(writer, columns.to_vec()).with_precision(precision)),
+            OutputFormat::Parquet => Box::new(ParquetSink::with_columns(writer, columns.to_vec())),
+            OutputFormat::Report => unreachable!(),
+        };
+        Ok(match &self.symbol {
+            Some(symbol) => Box::new(SymbolSink { symbol: symbol.clone(), inner: sink }),
+            None => sink,
+        })
+    }
+}
+
+///
+/// Passes on only the rows of one symbol.
+///
+struct SymbolSink {
+    symbol: String,
+    inner: Box<dyn OutputSink>,
+}
+
+impl OutputSink for SymbolSink {
+    fn write_row(&mut self, row: &StockRow) -> std::io::Result<()> {
+        if row.symbol == self.symbol {
+            self.inner.write_row(row)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.inner.finish()
+    }
+}
+
+impl FromStr for SinkSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rsplit_once(':') {
+            Some((path, format)) if !path.is_empty() => Ok(Self { path: path.to_string(), format: format.parse()?, symbol: None }),
+            _ => Err(format!("expected PATH:FORMAT, got '{}'", s)),
+        }
+    }
+}
+
+///
+/// Which of the rows to print to stdout in place of the echo.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Preview {
+    Head(usize),
+    Tail(usize),
+}
+
+impl Preview {
+    fn select<'a>(&self, rows: &'a [StockRow]) -> &'a [StockRow] {
+        match self {
+            Self::Head(n) => &rows[..(*n).min(rows.len())],
+            Self::Tail(n) => &rows[rows.len() - (*n).min(rows.len())..],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Params {
+    symbols: Vec<String>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    fetch: FetchOptions,
+    currency: Option<String>,
+    sinks: Vec<SinkSpec>,
+    sqlite: Option<String>,
+    settings: SignalSettings,
+    /// Rebase each symbol's prices to 100 at its first close, once it's fetched.
+    normalize: bool,
+    precision: usize,
+    buffer_size: usize,
+    quiet: bool,
+    incremental: bool,
+    alerts: Vec<Alert>,
+    strict: bool,
+    fail_fast: bool,
+    input_csv: Option<String>,
+    preview: Option<Preview>,
+    corr_matrix: Option<String>,
+    portfolio: Option<String>,
+    validate_output: bool,
+    summary_only: bool,
+    /// Where each symbol already written picks up, after its latest row in the outputs, in incremental mode.
+    watermarks: HashMap<String, DateTime<Utc>>,
+    rs_benchmark: Option<String>,
+    /// The bars of the `rs_benchmark`, fetched before the symbols.
+    benchmark: Option<Vec<Ohlcv>>,
+    watch: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self::from_opts(parse_opts())
+    }
+}
+
+fn parse_opts() -> Opts {
+    // clap exits with 2 on invalid arguments, which scripts would take for a breached alert
+    Opts::try_parse().and_then(validated).unwrap_or_else(|e| {
+        if !e.use_stderr() {
+            e.exit();
+        }
+        let _ = e.print();
+        std::process::exit(USAGE_EXIT_CODE);
+    })
+}
+
+///
+/// Reject the combinations of options clap can't express. The correlation matrix, the portfolio and the rel strength
+/// column join the symbols on the day, so an intraday interval's several bars a day would misalign them.
+///
+fn validated(opts: Opts) -> Result<Opts, clap::Error> {
+    let joined = [
+        ("--corr-matrix", opts.corr_matrix.is_some()),
+        ("--portfolio", opts.portfolio.is_some()),
+        ("--rs-benchmark", opts.rs_benchmark.is_some()),
+    ];
+    match joined.iter().find(|(_, given)| *given) {
+        Some((flag, _)) if opts.interval.is_intraday() => Err(Opts::command().error(
+            clap::error::ErrorKind::ArgumentConflict,
+            format!("{} joins the symbols on the day and can't be used with the intraday --interval {}", flag, opts.interval),
+        )),
+        _ => Ok(opts),
+    }
+}
+
+impl Params {
+    fn from_opts(opts: Opts) -> Self {
+        let config = opts.config.unwrap_or_default();
+        let default_symbols: Vec<String> = DEFAULT_SYMBOLS.iter().map(|symbol| symbol.to_string()).collect();
+        let mut symbols: Vec<String> = match (opts.symbols, config.symbols) {
+            (Some(symbols), _) => symbols.split(",").map(|v| v.trim().to_string()).collect(),
+            (None, Some(symbols)) => symbols.into_iter().map(|v| v.trim().to_string()).collect(),
+            (None, None) => default_symbols.into_iter().map(|v| v.to_string()).collect(),
+        };
+        symbols = deduplicated(symbols);
+        if opts.sort {
+            symbols.sort();
+        }
+        let sinks = if opts.sinks.is_empty() && opts.output_format == OutputFormat::Table {
+            vec![SinkSpec { path: "-".to_string(), format: OutputFormat::Table, symbol: None }]
+        } else if opts.sinks.is_empty() {
+            vec![SinkSpec { path: format!("data.{}", opts.output_format.extension()), format: opts.output_format, symbol: None }]
+        } else {
+            opts.sinks
+        };
+        let sinks = if opts.split_by_symbol {
+            sinks
+                .into_iter()
+                .flat_map(|sink| {
+                    if sink.is_stdout() || sink.is_report() {
+                        vec![sink]
+                    } else {
+                        symbols.iter().map(|symbol| sink.for_symbol(symbol)).collect()
+                    }
+                })
+                .collect()
+        } else {
+            sinks
+        };
+        // stdout belongs to a sink writing there, so the echo would only interleave with it
+        let quiet = opts.quiet || opts.summary_only || sinks.iter().any(SinkSpec::is_stdout);
+        let mut columns = if opts.columns.is_empty() { Column::DEFAULT.to_vec() } else { opts.columns };
+        if opts.annualized_vol && !columns.contains(&Column::AnnualizedVol) {
+            columns.push(Column::AnnualizedVol);
+        }
+        if opts.rs_benchmark.is_some() && !columns.contains(&Column::RelStrength) {
+            columns.push(Column::RelStrength);
+        }
+        // the next incremental run picks up after the last quote
+        if opts.incremental && !columns.contains(&Column::LastQuote) {
+            columns.push(Column::LastQuote);
+        }
+        for alert in &opts.alerts {
+            if !columns.contains(&alert.column) {
+                columns.push(alert.column);
+            }
+        }
+        let defaults = SignalSettings::default();
+        let trading_days_per_year = config.trading_days_per_year.unwrap_or(defaults.trading_days_per_year);
+        // a configured number of trading days is kept for daily bars and scales the intraday ones
+        let periods_per_year = match opts.resample {
+            Some(period) => period.annualization_factor(),
+            None if matches!(opts.interval, Interval::OneWeek | Interval::OneMonth) => opts.interval.annualization_factor(),
+            None => opts.interval.annualization_factor() * trading_days_per_year / TRADING_DAYS_PER_YEAR,
+        };
+        let settings = SignalSettings {
+            columns,
+            sma_window: opts.sma_window.or(config.sma_window).unwrap_or(defaults.sma_window),
+            rsi_period: defaults.rsi_period,
+            trading_days_per_year,
+            periods_per_year,
+            risk_free_rate: config.risk_free_rate.unwrap_or(defaults.risk_free_rate),
+            timezone: opts.timezone,
+        };
+        let now = Utc::now();
+        let sinks: Vec<SinkSpec> = if opts.output_append_timestamp {
+            sinks.into_iter().map(|sink| if sink.is_stdout() { sink } else { sink.timestamped(&now) }).collect()
+        } else {
+            sinks
+        };
+        let lookback = opts.interval.default_lookback().max(opts.resample.map_or(TimeDelta::zero(), |period| period.default_lookback()));
+        let default_start: DateTime<Utc> = now - lookback;
+        let start: DateTime<Utc> = match opts.from {
+            Some(from) => from.parse().unwrap_or(default_start),
+            None => default_start
+        };
+        let default_end: DateTime<Utc> = now;
+        let end = match opts.to {
+            Some(to) => to.parse().unwrap_or(default_end),
+            None => default_end,
+        };
+        let (start, end) = match start.cmp(&end) {
+            Ordering::Greater => (end, start),
+            // an empty range would skip every symbol, so take the dates as that one day
+            Ordering::Equal => (start, start + TimeDelta::days(1)),
+            Ordering::Less => (start, end),
+        };
+
+        Self {
+            symbols,
+            start,
+            end,
+            normalize: opts.normalize,
+            fetch: FetchOptions {
+                timeout: tokio_time::Duration::from_secs(opts.timeout),
+                fill_gaps: opts.fill_gaps,
+                resample: opts.resample,
+                price_source: opts.price_source.unwrap_or(if opts.use_adjusted { PriceSource::Adjclose } else { PriceSource::Close }),
+                limit_rows: opts.limit_rows,
+                interval: opts.interval,
+                jitter: tokio_time::Duration::from_millis(opts.jitter),
+                max_gap_days: opts.max_gap_days,
+                on_bad_data: opts.on_bad_data,
+                skip_sparse: opts.strict,
+                include_prepost: opts.include_prepost,
+            },
+            currency: opts.currency.map(|currency| currency.trim().to_uppercase()).filter(|currency| currency != "USD"),
+            sinks,
+            sqlite: opts.sqlite,
+            settings,
+            precision: opts.precision,
+            buffer_size: opts.buffer_size,
+            quiet,
+            incremental: opts.incremental,
+            alerts: opts.alerts,
+            strict: opts.strict,
+            fail_fast: opts.fail_fast,
+            input_csv: opts.input_csv,
+            corr_matrix: opts.corr_matrix,
+            portfolio: opts.portfolio,
+            validate_output: opts.validate_output,
+            summary_only: opts.summary_only,
+            preview: opts.head.map(Preview::Head).or(opts.tail.map(Preview::Tail)),
+            watermarks: HashMap::new(),
+            rs_benchmark: opts.rs_benchmark.map(|symbol| symbol.trim().to_uppercase()),
+            benchmark: None,
+            watch: opts.watch,
+        }
+    }
+}
+
+///
+/// Retrieve data from a data source and extract the closing prices. A provider taking longer than `timeout` is abandoned
+/// with `FetchError::TimedOut`.
+///
+async fn fetch_closing_data(
+    provider: &dyn QuoteProvider,
+    symbol: &str,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+    options: &FetchOptions,
+) -> Result<Vec<f64>, FetchError> {
+    Ok(fetch_bars(provider, symbol, start, end, options).await?.iter().map(|bar| bar.close).collect())
+}
+
+///
+/// A random wait of up to `max`, in whole milliseconds.
+///
+fn jitter_delay<R: Rng + ?Sized>(rng: &mut R, max: tokio_time::Duration) -> tokio_time::Duration {
+    tokio_time::Duration::from_millis(rng.random_range(0..=max.as_millis() as u64))
+}
+
+///
+/// Retrieve the time ordered bars the closing prices are taken from, after the jitter if there is one. The timeout only
+/// starts once the request is made.
+///
+async fn fetch_bars(
+    provider: &dyn QuoteProvider,
+    symbol: &str,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+    options: &FetchOptions,
+) -> Result<Vec<Ohlcv>, FetchError> {
+    if !options.jitter.is_zero() {
+        let delay = jitter_delay(&mut rand::rng(), options.jitter);
+        tokio_time::sleep(delay).await;
+    }
+    let mut quotes = tokio_time::timeout(options.timeout, provider.get_quote_history(symbol, start, end)).await
+        .map_err(|_| FetchError::TimedOut)??;
+    if !quotes.is_empty() {
+        quotes.sort_by_cached_key(|k| k.timestamp);
+        let bars = to_bars(&quotes, options.price_source);
+        let bad = bars.len() - bars.iter().filter(|bar| is_valid_close(bar.close)).count();
+        let bars = match options.on_bad_data {
+            _ if bad == 0 => bars,
+            BadDataPolicy::Error => return Err(FetchError::BadData(bad)),
+            BadDataPolicy::SkipSymbol => {
+                eprintln!("\n{} left out, {} close(s) not a positive number", symbol, bad);
+                return Ok(vec![]);
+            }
+            BadDataPolicy::DropPoints => {
+                eprintln!("\n{} dropped {} quote(s) without a positive close", symbol, bad);
+                bars.into_iter().filter(|bar| is_valid_close(bar.close)).collect()
+            }
+        };
+        if let Some(days) = options.max_gap_days.and_then(|max| longest_gap(&bars).filter(|days| *days > max)) {
+            if options.skip_sparse {
+                return Err(FetchError::Sparse(days));
+            }
+            eprintln!("\n{} has a gap of {} days between quotes, signal windows span it", symbol, days);
+        }
+        let (bars, missing) = match options.interval {
+            Interval::OneDay => bars_with_gaps(&bars, options.fill_gaps),
+            _ => (bars, 0),
+        };
+        if missing > 0 && !options.fill_gaps {
+            eprintln!("\n{} is missing {} trading day(s), signal windows span the gaps (see --fill-gaps)", symbol, missing);
+        }
+        let bars = match options.resample {
+            Some(period) => period.apply(&bars),
+            None => bars,
+        };
+        let recent = bars.len() - options.limit_rows.unwrap_or(bars.len()).min(bars.len());
+        Ok(bars[recent..].to_vec())
+    } else {
+        Ok(vec![])
+    }
+}
+
+///
+/// Fetch the `--rs-benchmark` over the whole range for the rel strength column. A benchmark which can't be fetched is
+/// warned about and the column left out, rather than failing the symbols compared to it.
+///
+async fn load_benchmark(params: &mut Params, provider: &dyn QuoteProvider) {
+    let Some(symbol) = &params.rs_benchmark else {
+        return;
+    };
+    match fetch_bars(provider, symbol, &params.start, &params.end, &params.fetch.reference()).await {
+        Ok(bars) if !bars.is_empty() => params.benchmark = Some(bars),
+        result => {
+            let reason = result.err().unwrap_or(FetchError::Empty);
+            eprintln!("\n{}: {}, the rel strength column is left out", symbol, reason);
+            params.settings.columns.retain(|column| *column != Column::RelStrength);
+        }
+    }
+}
+
+///
+/// The growth of `bars` over that of `benchmark` between the first and last days both have a close, i.e. the last
+/// point of the ratio of the two rebased to 1. `None` without a day in common or when either starts at 0.
+///
+fn relative_strength(bars: &[Ohlcv], benchmark: &[Ohlcv]) -> Option<f64> {
+    let closes: BTreeMap<NaiveDate, f64> = benchmark.iter().map(|bar| (day_of(bar.timestamp), bar.close)).collect();
+    let common: Vec<(f64, f64)> = bars.iter().filter_map(|bar| closes.get(&day_of(bar.timestamp)).map(|close| (bar.close, *close))).collect();
+    let ((first, first_benchmark), (last, last_benchmark)) = (common.first()?, common.last()?);
+    if *first == 0.0 || *first_benchmark == 0.0 || *last_benchmark == 0.0 {
+        return None;
+    }
+    Some((last / first) / (last_benchmark / first_benchmark))
+}
+
+///
+/// Weekdays strictly between the days of two quote timestamps. Exchange holidays count as missing too.
+///
+fn missing_trading_days(prev: u64, next: u64) -> usize {
+    let next = day_of(next);
+    day_of(prev).iter_days().skip(1).take_while(|date| *date < next).filter(is_weekday).count()
+}
+
+///
+/// The most calendar days between consecutive time ordered bars, `None` for fewer than two bars.
+///
+fn longest_gap(bars: &[Ohlcv]) -> Option<i64> {
+    bars.windows(2).map(|pair| (day_of(pair[1].timestamp) - day_of(pair[0].timestamp)).num_days()).max()
+}
+
+fn day_of(timestamp: u64) -> NaiveDate {
+    DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default().date_naive()
+}
+
+fn is_weekday(date: &NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+///
+/// How quotes are fetched and prepared before the signals run.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FetchOptions {
+    timeout: tokio_time::Duration,
+    fill_gaps: bool,
+    resample: Option<Resample>,
+    /// The price each bar closes at.
+    price_source: PriceSource,
+    /// Keep only this many of the most recent closes.
+    limit_rows: Option<usize>,
+    /// Gaps are only looked for between daily bars.
+    interval: Interval,
+    /// The longest random wait before a request.
+    jitter: tokio_time::Duration,
+    /// Whether intraday quotes include the extended hours, which the provider is built with.
+    include_prepost: bool,
+    /// The most calendar days allowed between consecutive bars before the symbol is warned about.
+    max_gap_days: Option<i64>,
+    /// Fail symbols over `max_gap_days` rather than only warning.
+    skip_sparse: bool,
+    on_bad_data: BadDataPolicy,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            timeout: tokio_time::Duration::from_secs(30),
+            fill_gaps: false,
+            resample: None,
+            price_source: PriceSource::Adjclose,
+            limit_rows: None,
+            interval: Interval::OneDay,
+            jitter: tokio_time::Duration::ZERO,
+            include_prepost: false,
+            max_gap_days: None,
+            skip_sparse: false,
+            on_bad_data: BadDataPolicy::DropPoints,
+        }
+    }
+}
+
+impl FetchOptions {
+    ///
+    /// The options for a reference series such as an FX rate or a benchmark, which is taken as the provider serves it:
+    /// only the timeout and interval carry over, so none of the symbols' preparation rescales or rejects it.
+    ///
+    fn reference(&self) -> Self {
+        Self { timeout: self.timeout, interval: self.interval, ..Self::default() }
+    }
+}
+
+///
+/// A fetched symbol's bars rebased to 100 when `normalize` is set, or as they are when no close is nonzero.
+///
+fn rebased(symbol: &str, bars: Vec<Ohlcv>, normalize: bool) -> Vec<Ohlcv> {
+    if !normalize || bars.is_empty() {
+        return bars;
+    }
+    normalized(&bars).unwrap_or_else(|| {
+        eprintln!("\n{} has no nonzero close to rebase on, prices left as they are", symbol);
+        bars
+    })
+}
+
+///
+/// The bars with every price scaled so that the first nonzero close is 100; leading zero closes stay 0. `None` when no
+/// close is nonzero.
+///
+fn normalized(bars: &[Ohlcv]) -> Option<Vec<Ohlcv>> {
+    let base = bars.iter().map(|bar| bar.close).find(|close| *close != 0.0)?;
+    let scale = 100.0 / base;
+    Some(
+        bars.iter()
+            .map(|bar| Ohlcv { open: bar.open * scale, high: bar.high * scale, low: bar.low * scale, close: bar.close * scale, ..*bar })
+            .collect(),
+    )
+}
+
+///
+/// Whether the signals can be calculated on a close: a NaN would spread through every window it falls in, and the
+/// returns and ratios divide by the closes.
+///
+fn is_valid_close(close: f64) -> bool {
+    close.is_finite() && close > 0.0
+}
+
+///
+/// Bars of the quotes closing at the price from `source`.
+///
+fn to_bars(quotes: &[Quote], source: PriceSource) -> Vec<Ohlcv> {
+    quotes
+        .iter()
+        .map(|q| Ohlcv {
+            timestamp: q.timestamp,
+            open: q.open,
+            high: q.high,
+            low: q.low,
+            close: source.price(q),
+            volume: q.volume,
+        })
+        .collect()
+}
+
+///
+/// Time ordered bars with the number of trading days missing between them. With `fill` each missing weekday gets a
+/// flat bar at the previous close and no volume.
+///
+fn bars_with_gaps(quotes: &[Ohlcv], fill: bool) -> (Vec<Ohlcv>, usize) {
+    let mut bars = Vec::with_capacity(quotes.len());
+    let mut missing = 0;
+    for (i, quote) in quotes.iter().enumerate() {
+        if i > 0 {
+            let prev = &quotes[i - 1];
+            let gap = missing_trading_days(prev.timestamp, quote.timestamp);
+            missing += gap;
+            if fill {
+                let close = prev.close;
+                bars.extend(
+                    (1..)
+                        .map(|n: u64| prev.timestamp + n * 86_400)
+                        .filter(|timestamp| is_weekday(&day_of(*timestamp)))
+                        .take(gap)
+                        .map(|timestamp| Ohlcv { timestamp, open: close, high: close, low: close, close, volume: 0 }),
+                );
+            }
+        }
+        bars.push(*quote);
+    }
+    (bars, missing)
+}
+
+///
+/// How long every fetch is held back after a rate limited response.
+///
+const RATE_LIMIT_COOLDOWN: tokio_time::Duration = tokio_time::Duration::from_secs(5);
+
+///
+/// The most times a symbol is requested while its fetch keeps failing in a way worth retrying.
+///
+const FETCH_ATTEMPTS: u32 = 5;
+
+///
+/// Make a fetch through the throttle, feeding its outcome back and trying again while it fails in a way worth retrying:
+/// after the cooldown while the provider rate limits it, and straight away after a network error.
+///
+/// # Returns
+///
+/// The outcome of the last attempt, with how many attempts it took and how long they took.
+///
+async fn throttled<T, F, Fut>(throttle: &Throttle, fetch: F) -> (Result<T, FetchError>, FetchTelemetry)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, FetchError>>,
+{
+    let mut telemetry = FetchTelemetry::default();
+    loop {
+        let permit = throttle.acquire().await;
+        let started = tokio_time::Instant::now();
+        let result = fetch().await;
+        telemetry.latency += started.elapsed();
+        drop(permit);
+        match result {
+            Err(e) if e.is_retryable() => {
+                if e == FetchError::RateLimited {
+                    throttle.rate_limited();
+                }
+                if telemetry.retries + 1 == FETCH_ATTEMPTS {
+                    return (Err(e), telemetry);
+                }
+                telemetry.retries += 1;
+            }
+            result => {
+                throttle.succeeded();
+                return (result, telemetry);
+            }
+        }
+    }
+}
+
+///
+/// Fetch the bars of every symbol concurrently, in the order of `params.symbols`, backing off when rate limited.
+/// Symbols without data are reported and left out.
+///
+async fn fetch_all_bars(params: &Params, provider: Arc<dyn QuoteProvider>) -> Vec<(String, Vec<Ohlcv>)> {
+    let (start, end, options, normalize) = (params.start, params.end, params.fetch, params.normalize);
+    let throttle = Arc::new(Throttle::new(params.symbols.len(), RATE_LIMIT_COOLDOWN));
+    let mut fetches = JoinSet::new();
+    for (index, symbol) in params.symbols.iter().enumerate() {
+        let (provider, symbol, throttle) = (provider.clone(), symbol.clone(), throttle.clone());
+        fetches.spawn(async move {
+            let (bars, _) = throttled(&throttle, || fetch_bars(provider.as_ref(), &symbol, &start, &end, &options)).await;
+            (index, bars.map(|bars| rebased(&symbol, bars, normalize)))
+        });
+    }
+
+    let mut fetched = BTreeMap::new();
+    while let Some(bars) = fetches.join_next().await {
+        match bars {
+            Ok((_, Ok(bars))) if bars.is_empty() => {}
+            Ok((index, Ok(bars))) => {
+                fetched.insert(index, bars);
+            }
+            Ok((index, Err(e))) => report_fetch_error(&params.symbols[index], &e),
+            Err(e) => eprintln!("\nfetch task failed: {}", e),
+        }
+    }
+    fetched.into_iter().map(|(index, bars)| (params.symbols[index].clone(), bars)).collect()
+}
+
+///
+/// Fetch every symbol and calculate its full series for a report.
+///
+async fn build_report(params: &Params, provider: Arc<dyn QuoteProvider>) -> Report {
+    let parameters = ReportParameters { window: params.settings.sma_window, rsi_period: params.settings.rsi_period };
+    let mut symbols = BTreeMap::new();
+    for (symbol, bars) in fetch_all_bars(params, provider).await {
+        let closes = bars.iter().map(|bar| bar.close).collect();
+        symbols.insert(symbol, SymbolReport::calculate(closes, &parameters).await);
+    }
+    Report { start: params.start.to_rfc3339(), end: params.end.to_rfc3339(), parameters, symbols }
+}
+
+///
+/// The fewest returns in common a correlation is calculated over.
+///
+const MIN_CORRELATION_OBSERVATIONS: usize = 3;
+
+///
+/// Fetch every symbol and write the correlation matrix of their returns to `path`, warning about any symbol excluded
+/// for sharing too few days with the others.
+///
+async fn write_correlation_matrix(params: &Params, provider: Arc<dyn QuoteProvider>, path: &str) -> std::io::Result<CorrelationMatrix> {
+    let series = fetch_all_bars(params, provider).await;
+    let (matrix, excluded) = CorrelationMatrix::calculate(&series, MIN_CORRELATION_OBSERVATIONS);
+    for symbol in excluded {
+        eprintln!("\n{} excluded from the correlation matrix, fewer than {} returns in common with the other symbols", symbol, MIN_CORRELATION_OBSERVATIONS);
+    }
+    let contents = if path.ends_with(".json") {
+        serde_json::to_string_pretty(&matrix)? + "\n"
+    } else {
+        matrix.to_csv(params.precision.max(4))
+    };
+    std::fs::write(path, contents)?;
+    Ok(matrix)
+}
+
+///
+/// Fetch every symbol and write their equal weight equity curve to `path`, printing its return and drawdown.
+///
+async fn write_portfolio(params: &Params, provider: Arc<dyn QuoteProvider>, path: &str) -> std::io::Result<Portfolio> {
+    let portfolio = Portfolio::calculate(&fetch_all_bars(params, provider).await);
+    let contents = if path.ends_with(".json") {
+        serde_json::to_string_pretty(&portfolio)? + "\n"
+    } else {
+        portfolio.to_csv(params.precision.max(4))
+    };
+    std::fs::write(path, contents)?;
+    if !params.quiet {
+        println!(
+            "portfolio of {} over {} days: cumulative return {:.2}%, max drawdown {:.2}%",
+            portfolio.symbols.len(), portfolio.dates.len(), portfolio.cumulative_return * 100.0, portfolio.max_drawdown * 100.0
+        );
+    }
+    Ok(portfolio)
+}
+
+///
+/// Write the report as pretty printed JSON to the sink's path, or stdout for `-`.
+///
+fn write_report(report: &Report, sink: &SinkSpec, buffer_size: usize) -> std::io::Result<()> {
+    let mut writer: Box<dyn Write> = if sink.is_stdout() {
+        Box::new(std::io::stdout())
+    } else {
+        Box::new(buffered(std::fs::File::create(&sink.path)?, buffer_size))
+    };
+    serde_json::to_writer_pretty(&mut writer, report)?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+///
+/// The parameters of the run, as of `generated_at`.
+///
+fn run_metadata(params: &Params, generated_at: DateTime<Utc>, conversion: Option<&CurrencyConversion>) -> RunMetadata {
+    RunMetadata {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        generated_at: generated_at.to_rfc3339(),
+        symbols: params.symbols.clone(),
+        start: params.start.to_rfc3339(),
+        end: params.end.to_rfc3339(),
+        interval: params.fetch.interval.to_string(),
+        resample: params.fetch.resample.map(|period| period.as_str().to_string()),
+        windows: SignalWindows { sma_window: params.settings.sma_window, rsi_period: params.settings.rsi_period },
+        columns: params.settings.columns.iter().map(|column| column.name().to_string()).collect(),
+        conversion: conversion.cloned(),
+    }
+}
+
+///
+/// Write the run's metadata, and the rate prices were converted at if they were, beside every output that went to a file
+/// rather than stdout.
+///
+fn write_metadata(params: &Params, generated_at: DateTime<Utc>, conversion: Option<&CurrencyConversion>) -> std::io::Result<()> {
+    let metadata = run_metadata(params, generated_at, conversion);
+    let files = params.sinks.iter().filter(|sink| !sink.is_stdout()).map(|sink| sink.path.as_str());
+    for path in files.chain(params.sqlite.as_deref()) {
+        metadata.write_beside(path)?;
+    }
+    Ok(())
+}
+
+///
+/// Check CSV written by a `CsvSink` parses back into exactly `expected_rows` rows, with a finite number in every numeric
+/// cell that isn't empty.
+///
+fn validate_csv(contents: &str, expected_rows: usize) -> Result<(), String> {
+    let mut lines = contents.lines();
+    let columns = lines
+        .next()
+        .ok_or("there's no header")?
+        .split(',')
+        .map(|header| Column::from_header(header).ok_or_else(|| format!("unknown column '{}' in the header", header)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut rows = 0;
+    for (index, line) in lines.enumerate() {
+        let row = StockRow::from_csv_columns(line, &columns).map_err(|e| format!("row {}: {}", index + 1, e))?;
+        if let Some(column) = columns.iter().find(|column| row.value(**column).is_some_and(|value| !value.is_finite())) {
+            return Err(format!("row {}: {} isn't finite", index + 1, column.name()));
+        }
+        rows += 1;
+    }
+    if rows != expected_rows {
+        return Err(format!("{} rows, expected {}", rows, expected_rows));
+    }
+    Ok(())
+}
+
+///
+/// The CSV files among the sinks, which `--validate-output` reads back.
+///
+fn csv_files(params: &Params) -> impl Iterator<Item = &SinkSpec> {
+    params.sinks.iter().filter(|sink| sink.format == OutputFormat::Csv && !sink.is_stdout())
+}
+
+///
+/// The rows in each CSV file before the run, which an incremental run appends to.
+///
+fn existing_csv_rows(params: &Params) -> HashMap<String, usize> {
+    csv_files(params)
+        .filter(|_| params.incremental)
+        .filter_map(|sink| std::fs::read_to_string(&sink.path).ok().map(|contents| (sink.path.clone(), contents.lines().count().saturating_sub(1))))
+        .collect()
+}
+
+///
+/// Check every CSV file holds the rows it had before the run and those the run wrote to it, returning the problem with
+/// each that doesn't.
+///
+fn validate_outputs(params: &Params, summary: &StreamSummary, existing: &HashMap<String, usize>) -> Vec<String> {
+    csv_files(params)
+        .filter_map(|sink| {
+            let written = match &sink.symbol {
+                Some(symbol) => usize::from(!summary.failed.iter().any(|(failed, _)| failed == symbol)),
+                None => summary.rows,
+            };
+            let expected = existing.get(&sink.path).copied().unwrap_or(0) + written;
+            let checked = std::fs::read_to_string(&sink.path).map_err(|e| e.to_string()).and_then(|contents| validate_csv(&contents, expected));
+            checked.err().map(|e| format!("{}: {}", sink.path, e))
+        })
+        .collect()
+}
+
+///
+/// Where an incremental run picks up after a row: the second after its last quote, so that only newer quotes are
+/// fetched. A row written before the last quote was recorded only has its period start, and is picked up after at
+/// midnight UTC of the following day. `None` when neither is a timestamp.
+///
+fn next_period_start(period_start: &str, last_quote: Option<&str>) -> Option<DateTime<Utc>> {
+    let parse = |at: &str| DateTime::parse_from_rfc3339(at).map(|at| at.to_utc()).ok();
+    match last_quote.and_then(parse) {
+        Some(last_quote) => Some(last_quote + TimeDelta::seconds(1)),
+        None => parse(period_start).map(|start| (start.date_naive() + TimeDelta::days(1)).and_time(NaiveTime::MIN).and_utc()),
+    }
+}
+
+///
+/// Where each symbol's incremental fetch starts, after the latest row across the file sinks and SQLite database. Where
+/// the outputs disagree the earliest wins, so that none of them misses a period. Missing files have no watermarks.
+///
+fn read_watermarks(params: &Params) -> std::io::Result<HashMap<String, DateTime<Utc>>> {
+    let mut sources: Vec<Vec<(String, String, Option<String>)>> = Vec::new();
+    for sink in params.sinks.iter().filter(|sink| !sink.is_stdout()) {
+        let contents = match std::fs::read_to_string(&sink.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        sources.push(match sink.format {
+            OutputFormat::Csv => period_starts_from_csv(&contents),
+            OutputFormat::Json => period_starts_from_json(&contents)?,
+            OutputFormat::Table | OutputFormat::Parquet | OutputFormat::Report => continue,
+        });
+    }
+    if let Some(path) = &params.sqlite {
+        sources.push(SqliteSink::open(path)?.latest_period_starts()?);
+    }
+
+    let mut watermarks: HashMap<String, DateTime<Utc>> = HashMap::new();
+    for source in sources {
+        let mut latest: HashMap<String, DateTime<Utc>> = HashMap::new();
+        for (symbol, start, last_quote) in source {
+            if let Some(start) = next_period_start(&start, last_quote.as_deref()) {
+                latest.entry(symbol).and_modify(|latest| *latest = (*latest).max(start)).or_insert(start);
+            }
+        }
+        for (symbol, start) in latest {
+            watermarks.entry(symbol).and_modify(|earliest| *earliest = (*earliest).min(start)).or_insert(start);
+        }
+    }
+    Ok(watermarks)
+}
+
+///
+/// The `(symbol, period start, last quote)` of each row of CSV written by a `CsvSink`, located by the column headers.
+///
+fn period_starts_from_csv(contents: &str) -> Vec<(String, String, Option<String>)> {
+    let mut lines = contents.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().split(',').collect();
+    let position = |column: Column| header.iter().position(|h| *h == column.header());
+    let (Some(symbol), Some(start)) = (position(Column::Symbol), position(Column::PeriodStart)) else {
+        return vec![];
+    };
+    let last_quote = position(Column::LastQuote);
+    lines
+        .filter_map(|line| {
+            let cells: Vec<&str> = line.split(',').collect();
+            let last_quote = last_quote.and_then(|i| cells.get(i)).filter(|cell| !cell.is_empty()).map(|cell| cell.to_string());
+            Some((cells.get(symbol)?.to_string(), cells.get(start)?.to_string(), last_quote))
+        })
+        .collect()
+}
+
+///
+/// The `(symbol, period start, last quote)` of each row of newline delimited JSON written by a `JsonSink`.
+///
+fn period_starts_from_json(contents: &str) -> std::io::Result<Vec<(String, String, Option<String>)>> {
+    let mut starts = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let row: serde_json::Value = serde_json::from_str(line)?;
+        if let (Some(symbol), Some(start)) = (row["symbol"].as_str(), row["period_start"].as_str()) {
+            starts.push((symbol.to_string(), start.to_string(), row["last_quote"].as_str().map(str::to_string)));
+        }
+    }
+    Ok(starts)
+}
+
+///
+/// The Yahoo symbol quoting `currency` per US dollar.
+///
+fn fx_symbol(currency: &str) -> String {
+    format!("USD{}=X", currency)
+}
+
+///
+/// Write all rows to the sink and finish it.
+///
+fn write_signals(sink: &mut dyn OutputSink, rows: &[StockRow]) -> std::io::Result<()> {
+    for row in rows {
+        sink.write_row(row)?;
+    }
+    sink.finish()
+}
+
+///
+/// A bar on stderr counting the symbols fetched so far, hidden when running quietly or when stdout isn't a terminal
+/// (i.e. the output is being piped or redirected elsewhere).
+///
+fn progress_bar(params: &Params, interactive: bool) -> ProgressBar {
+    if params.quiet || !interactive {
+        return ProgressBar::hidden();
+    }
+    let style = ProgressStyle::with_template("{bar:40} {pos}/{len} symbols, eta {eta}").unwrap_or_else(|_| ProgressStyle::default_bar());
+    ProgressBar::new(params.symbols.len() as u64).with_style(style)
+}
+
+///
+/// The exit code of a run with invalid arguments or config, or which couldn't write its output.
+///
+const USAGE_EXIT_CODE: i32 = 1;
+
+///
+/// The exit code of a run in which any `--alert` was breached.
+///
+const ALERT_EXIT_CODE: i32 = 2;
+
+///
+/// The exit code of a run in which no symbol could be fetched.
+///
+const FAILED_EXIT_CODE: i32 = 3;
+
+///
+/// The exit code of a run in which some symbols couldn't be fetched.
+///
+const PARTIAL_EXIT_CODE: i32 = 4;
+
+///
+/// The exit code of a run in which `--validate-output` found a file that doesn't hold the rows written.
+///
+const INVALID_OUTPUT_EXIT_CODE: i32 = 5;
+
+///
+/// What a run of `stream_signals` wrote, which symbols it couldn't write a row for, and how each symbol's fetch went.
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+struct StreamSummary {
+    rows: usize,
+    failed: Vec<(String, FetchError)>,
+    breached: usize,
+    telemetry: BTreeMap<String, FetchTelemetry>,
+    conversion: Option<CurrencyConversion>,
+}
+
+impl StreamSummary {
+    ///
+    /// The process exit code for the run: every symbol failing beats a breached alert, which beats some symbols failing.
+    /// Symbols without any data in the range only count as failed when `strict`.
+    ///
+    fn exit_code(&self, strict: bool) -> i32 {
+        let failed = self.failed.iter().filter(|(_, e)| strict || *e != FetchError::Empty).count();
+        if failed > 0 && self.rows == 0 {
+            FAILED_EXIT_CODE
+        } else if self.breached > 0 {
+            ALERT_EXIT_CODE
+        } else if failed > 0 {
+            PARTIAL_EXIT_CODE
+        } else {
+            0
+        }
+    }
+}
+
+///
+/// Write the counts of a run over `symbols` symbols, with the symbols which failed and the time spent fetching.
+///
+fn write_summary(summary: &StreamSummary, symbols: usize, console: &mut dyn Write) -> std::io::Result<()> {
+    writeln!(console, "symbols: {}", symbols)?;
+    writeln!(console, "rows: {}", summary.rows)?;
+    let failed: Vec<String> = summary.failed.iter().map(|(symbol, e)| format!("{} ({})", symbol, e)).collect();
+    writeln!(console, "failed: {}{}", failed.len(), if failed.is_empty() { String::new() } else { format!(", {}", failed.join(", ")) })?;
+    writeln!(console, "alerts breached: {}", summary.breached)?;
+    writeln!(console, "retries: {}", summary.telemetry.values().map(|telemetry| telemetry.retries).sum::<u32>())?;
+    let latency: tokio_time::Duration = summary.telemetry.values().map(|telemetry| telemetry.latency).sum();
+    match summary.telemetry.iter().max_by_key(|(_, telemetry)| telemetry.latency) {
+        Some((symbol, slowest)) => {
+            writeln!(console, "fetch time: {:.3}s, slowest {} {:.3}s", latency.as_secs_f64(), symbol, slowest.latency.as_secs_f64())
+        }
+        None => writeln!(console, "fetch time: 0.000s"),
+    }
+}
+
+///
+/// Run the whole pipeline for `--summary-only`, writing the summary to `console` in place of any rows.
+///
+async fn summarize(params: &Params, provider: Arc<dyn QuoteProvider>, console: &mut dyn Write) -> std::io::Result<StreamSummary> {
+    let summary = stream_signals(params, provider, &mut [], &mut std::io::sink()).await?;
+    write_summary(&summary, params.symbols.len(), console)?;
+    Ok(summary)
+}
+
+///
+/// The explanation printed when symbols were fetched but none of them had data for a row.
+///
+fn empty_result_warning(params: &Params) -> String {
+    format!(
+        "warning: no rows were written, none of {} had quotes between {} and {}. The market may have been closed for the \
+         whole range (a weekend or holiday), the dates may be wrong or in the future, or the symbols may not exist",
+        params.symbols.join(", "),
+        params.start.format("%Y-%m-%d"),
+        params.end.format("%Y-%m-%d"),
+    )
+}
+
+///
+/// A line for each alert a row breaches, in row order.
+///
+fn alert_messages(alerts: &[Alert], rows: &[StockRow]) -> Vec<String> {
+    rows.iter()
+        .flat_map(|row| {
+            alerts.iter().filter(|alert| alert.breached(row)).map(move |alert| {
+                format!("ALERT {}: {} is {:.2}, breaching {}", row.symbol, alert.column.name(), row.value(alert.column).unwrap_or_default(), alert)
+            })
+        })
+        .collect()
+}
+
+///
+/// Write the signals for every symbol to each of the `sinks`, echoing them to `console` as CSV unless running quietly,
+/// or only the `--head` or `--tail` of them as a table. Breached alerts are reported on stderr once the rows are written, as is a run in which no symbol had any data.
+///
+/// Every symbol is fetched whatever happens to the others, unless failing fast; only the rows of those that succeeded are
+/// written. A rate limited response slows every fetch down rather than failing the symbol.
+///
+/// # Returns
+///
+/// The number of rows written and alerts breached, the symbols which failed, and the telemetry of each fetch. A run
+/// failing fast returns nothing but the failure it stopped at.
+///
+async fn stream_signals(
+    params: &Params,
+    provider: Arc<dyn QuoteProvider>,
+    sinks: &mut [Box<dyn OutputSink>],
+    console: &mut dyn Write,
+) -> std::io::Result<StreamSummary> {
+    let (start, end, options, normalize) = (params.start, params.end, params.fetch, params.normalize);
+    let progress = progress_bar(params, std::io::stdout().is_terminal());
+    let throttle = Arc::new(Throttle::new(params.symbols.len(), RATE_LIMIT_COOLDOWN));
+    let telemetry = Arc::new(std::sync::Mutex::new(BTreeMap::new()));
+    let benchmark = params.benchmark.clone().map(Arc::new);
+    let relative = Arc::new(std::sync::Mutex::new(HashMap::new()));
+    let last_quotes = Arc::new(std::sync::Mutex::new(HashMap::new()));
+    // symbols sharing a start are fetched together; without watermarks that is all of them
+    let mut batches: BTreeMap<DateTime<Utc>, Vec<usize>> = BTreeMap::new();
+    for (index, symbol) in params.symbols.iter().enumerate() {
+        let start = params.watermarks.get(symbol).copied().unwrap_or(start);
+        batches.entry(start).or_default().push(index);
+    }
+    let mut collected = BTreeMap::new();
+    let mut fetched = false;
+    for (start, indices) in batches {
+        if start >= end {
+            progress.inc(indices.len() as u64);
+            continue;
+        }
+        fetched = true;
+        let symbols: Vec<String> = indices.iter().map(|index| params.symbols[*index].clone()).collect();
+        let rows = collect_rows(&symbols, &start, &params.settings, params.fail_fast, |symbol| {
+            let (provider, progress, throttle, telemetry) = (provider.clone(), progress.clone(), throttle.clone(), telemetry.clone());
+            let (benchmark, relative, last_quotes) = (benchmark.clone(), relative.clone(), last_quotes.clone());
+            async move {
+                let (bars, fetch) = throttled(&throttle, || fetch_bars(provider.as_ref(), &symbol, &start, &end, &options)).await;
+                let bars = bars.map(|bars| rebased(&symbol, bars, normalize));
+                if let (Ok(bars), Some(benchmark)) = (&bars, benchmark.as_deref()) {
+                    if let Some(ratio) = relative_strength(bars, benchmark) {
+                        relative.lock().unwrap().insert(symbol.clone(), ratio);
+                    }
+                }
+                if let Some(last) = bars.as_ref().ok().and_then(|bars| bars.last()) {
+                    last_quotes.lock().unwrap().insert(symbol.clone(), last.timestamp);
+                }
+                telemetry.lock().unwrap().insert(symbol, fetch);
+                progress.inc(1);
+                bars.map(|bars| bars.iter().map(|bar| bar.close).collect())
+            }
+        }).await;
+        collected.extend(rows.into_iter().map(|(index, row)| (indices[index], row)));
+        if params.fail_fast {
+            if let Some((index, Err(e))) = collected.iter().find(|(_, row)| matches!(row, Err(e) if *e != FetchError::Empty)) {
+                // nothing is written, so the run fails on this symbol alone whatever the others fetched
+                progress.finish_and_clear();
+                let symbol = params.symbols[*index].clone();
+                eprintln!("stopped at {} (--fail-fast)", symbol);
+                let telemetry = std::mem::take(&mut *telemetry.lock().unwrap());
+                return Ok(StreamSummary { failed: vec![(symbol, e.clone())], telemetry, ..Default::default() });
+            }
+        }
+    }
+    // cleared before anything is written, so the bar never ends up among the rows
+    progress.finish_and_clear();
+    let results: Vec<(String, Result<StockRow, FetchError>)> =
+        collected.into_iter().map(|(index, row)| (params.symbols[index].clone(), row)).collect();
+    let mut rows = Vec::with_capacity(results.len());
+    let mut failed = Vec::new();
+    for (symbol, result) in results {
+        match result {
+            Ok(row) => rows.push(row),
+            Err(e) => failed.push((symbol, e)),
+        }
+    }
+    if params.settings.columns.contains(&Column::RelStrength) {
+        let relative = relative.lock().unwrap();
+        rows.iter_mut().for_each(|row| row.rel_strength = relative.get(&row.symbol).copied());
+    }
+    let last_quotes = std::mem::take(&mut *last_quotes.lock().unwrap());
+    for row in rows.iter_mut() {
+        let at = last_quotes.get(&row.symbol).and_then(|at| DateTime::from_timestamp(*at as i64, 0));
+        row.last_quote = at.map(|at| match params.settings.timezone {
+            Some(zone) => at.with_timezone(&zone).to_rfc3339(),
+            None => at.to_rfc3339(),
+        });
+    }
+
+    let mut conversion = None;
+    if let Some(currency) = &params.currency {
+        let fx = fx_symbol(currency);
+        match fetch_closing_data(provider.as_ref(), &fx, &start, &end, &options.reference()).await.map(|closes| closes.last().copied()) {
+            Ok(Some(rate)) => {
+                eprintln!("\nprices converted to {} at {} ({})", currency, rate, fx);
+                rows.iter_mut().for_each(|row| row.convert(rate, currency));
+                conversion = Some(CurrencyConversion { currency: currency.clone(), fx_symbol: fx, rate });
+            }
+            Ok(None) => eprintln!("\n{}: no quotes in range, prices left in USD", fx),
+            Err(e) => eprintln!("\n{}: {}, prices left in USD", fx, e),
+        }
+    }
+
+    match params.preview {
+        _ if params.quiet => {}
+        Some(preview) => {
+            let selected = preview.select(&rows);
+            let mut table = TableSink::with_columns(&mut *console, params.settings.columns.clone()).with_precision(params.precision);
+            write_signals(&mut table, selected)?;
+            writeln!(console, "({} of {} rows)", selected.len(), rows.len())?;
+        }
+        None => {
+            let mut echo = CsvSink::with_columns(console, params.settings.columns.clone()).with_precision(params.precision);
+            write_signals(&mut echo, &rows)?;
+        }
+    }
+    for sink in sinks.iter_mut() {
+        write_signals(sink.as_mut(), &rows)?;
+    }
+    // an incremental run with every symbol up to date fetches nothing and has nothing to explain
+    if rows.is_empty() && fetched {
+        eprintln!("\n{}", empty_result_warning(params));
+    } else if !failed.is_empty() {
+        let symbols: Vec<&str> = failed.iter().map(|(symbol, _)| symbol.as_str()).collect();
+        eprintln!("\nno rows for {} of {} symbols: {}", failed.len(), params.symbols.len(), symbols.join(", "));
+    }
+    let alerts = alert_messages(&params.alerts, &rows);
+    for alert in &alerts {
+        eprintln!("{}", alert);
+    }
+    let telemetry = std::mem::take(&mut *telemetry.lock().unwrap());
+    Ok(StreamSummary { rows: rows.len(), failed, breached: alerts.len(), telemetry, conversion })
+}
+
+async fn get_sp500() -> Result<Vec<String>, Error> {
+    let mut file = fs::File::open("sp500.txt").await?;
+    let mut buffer = String::new();
+    file.read_to_string(&mut buffer).await?;
+    let data = buffer.split(",").map(|v| v.trim().to_string()).collect();
+    Ok(data)
+}
+
+///
+/// Print a symbol's signals as a CSV row, fetching again on the same failures as `throttled`, up to `FETCH_ATTEMPTS`
+/// times in all.
+///
+#[async_recursion]
+async fn print_signal_row(provider: Arc<dyn QuoteProvider>, symbol: &'static str, start: DateTime<Utc>, end: DateTime<Utc>, attempt: u32) -> () {
+    let closes = fetch_closing_data(provider.as_ref(), symbol, &start, &end, &FetchOptions::default()).await;
+    match closes {
+        Ok(closes) => {
+            if !closes.is_empty() {
+                let data = calculate_signals(symbol, &start, &closes, &SignalSettings::default()).await;
+                println!("{}", data.to_csv_row());
+            }
+        }
+        Err(e) if e.is_retryable() && attempt + 1 < FETCH_ATTEMPTS => {
+            task::spawn(print_signal_row(provider, symbol, start, end, attempt + 1));
+        }
+        Err(e) => eprintln!("{}: {}", symbol, e),
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let opts = parse_opts();
+    if let Some(command) = &opts.command {
+        return command.run();
+    }
+    let mut params = Params::from_opts(opts);
+    let provider: Arc<dyn QuoteProvider> = match &params.input_csv {
+        Some(template) => Arc::new(CsvProvider::new(template)),
+        None => Arc::new(YahooProvider::new()?.with_interval(params.fetch.interval).with_prepost(params.fetch.include_prepost)),
+    };
+    if params.watch {
+        watch_sp500(provider).await
+    } else if let Some(path) = &params.corr_matrix {
+        write_correlation_matrix(&params, provider, path).await.map(|_| ())
+    } else if let Some(path) = &params.portfolio {
+        write_portfolio(&params, provider, path).await.map(|_| ())
+    } else if params.summary_only {
+        let summary = summarize(&params, provider, &mut std::io::stdout()).await?;
+        match summary.exit_code(params.strict) {
+            0 => Ok(()),
+            code => std::process::exit(code),
+        }
+    } else {
+        if !params.quiet {
+            println!();
+        }
+        if params.incremental {
+            params.watermarks = read_watermarks(&params)?;
+        }
+        let generated_at = Utc::now();
+        load_benchmark(&mut params, provider.as_ref()).await;
+        let existing = existing_csv_rows(&params);
+        let reports: Vec<&SinkSpec> = params.sinks.iter().filter(|sink| sink.is_report()).collect();
+        if !reports.is_empty() {
+            let report = build_report(&params, provider.clone()).await;
+            for sink in reports {
+                write_report(&report, sink, params.buffer_size)?;
+            }
+        }
+        let mut sinks = params.sinks
+            .iter()
+            .filter(|sink| !sink.is_report())
+            .map(|sink| sink.open(&params.settings.columns, params.precision, params.incremental, params.buffer_size))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        if let Some(path) = &params.sqlite {
+            sinks.push(Box::new(SqliteSink::open(path)?));
+        }
+        // the report already covers every symbol, so only fetch again for rows someone will see
+        if sinks.is_empty() && params.quiet && params.alerts.is_empty() {
+            return write_metadata(&params, generated_at, None);
+        }
+        let summary = stream_signals(&params, provider, &mut sinks, &mut std::io::stdout()).await?;
+        drop(sinks);
+        write_metadata(&params, generated_at, summary.conversion.as_ref())?;
+        if params.validate_output {
+            let problems = validate_outputs(&params, &summary, &existing);
+            for problem in &problems {
+                eprintln!("invalid output {}", problem);
+            }
+            if !problems.is_empty() {
+                std::process::exit(INVALID_OUTPUT_EXIT_CODE);
+            }
+        }
+        match summary.exit_code(params.strict) {
+            0 => {}
+            code => std::process::exit(code),
+        }
+        Ok(())
+    }
+}
+
+async fn watch_sp500(provider: Arc<dyn QuoteProvider>) -> std::io::Result<()> {
+    let symbols = Box::leak(Box::new(get_sp500().await?));
+    let mut stream = IntervalStream::new(tokio_time::interval(tokio_time::Duration::from_secs(30)));
+
+    while stream.next().await.is_some() {
+        let end: DateTime<Utc> = Utc::now();
+        let start = end - TimeDelta::days(1);
+        for symbol in symbols.iter() {
+            task::spawn(print_signal_row(provider.clone(), symbol, start, end, 0));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+    use time::OffsetDateTime;
+    use yahoo_finance_api as yahoo;
+    use yahoo::time::macros::datetime;
+    use yahoo::YahooError;
+    use std::sync::Mutex;
+    use async_trait::async_trait;
+    use async_streams::Quote;
+    use super::*;
+
+    fn quote(timestamp: u64, close: f64) -> Quote {
+        Quote { timestamp, open: close, high: close, low: close, volume: 0, close, adjclose: close }
+    }
+
+    ///
+    /// Serves a fixed series for every symbol, stalling on `slow` symbols for `delay`.
+    ///
+    struct MockProvider {
+        quotes: Vec<Quote>,
+        slow: Vec<String>,
+        delay: tokio_time::Duration,
+    }
+
+    ///
+    /// Records the rows written to it, shared so they can be inspected once the sink is handed over.
+    ///
+    #[derive(Clone, Default)]
+    struct MemorySink {
+        rows: Arc<Mutex<Vec<StockRow>>>,
+        finished: Arc<Mutex<bool>>,
+    }
+
+    impl MemorySink {
+        fn boxed(&self) -> Box<dyn OutputSink> {
+            Box::new(self.clone())
+        }
+    }
+
+    impl OutputSink for MemorySink {
+        fn write_row(&mut self, row: &StockRow) -> std::io::Result<()> {
+            self.rows.lock().unwrap().push(row.clone());
+            Ok(())
+        }
+
+        fn finish(&mut self) -> std::io::Result<()> {
+            *self.finished.lock().unwrap() = true;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl QuoteProvider for MockProvider {
+        async fn get_quote_history(&self, symbol: &str, _start: &DateTime<Utc>, _end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+            if self.slow.iter().any(|s| s == symbol) {
+                tokio_time::sleep(self.delay).await;
+            }
+            Ok(self.quotes.clone())
+        }
+    }
+
+    #[ignore]
+    #[test]
+    fn it_loads_params() {
+        let params = Params::default();
+        //println!("{:?}", &params);
+        assert_eq!(params.symbols.len(), 5usize);
+    }
+
+    #[tokio::test]
+    async fn it_gets_closing_data() -> Result<(),Error>{
+        let symbol = "AAPL";
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let end: DateTime<Utc> = DateTime::from_str("2020-1-31 23:59:59.99 UTC").unwrap();
+        let provider = YahooProvider::new()?;
+        let data = fetch_closing_data(&provider, symbol, &start, &end, &FetchOptions::default()).await?;
+        assert!(!data.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_streams_signals() -> Result<(),Error>{
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL", "-f", "2020-01-01T00:00:00Z", "-t", "2020-01-31T23:59:59Z"]));
+        let mut sinks: Vec<Box<dyn OutputSink>> = vec![Box::new(JsonSink::new(std::io::stdout()))];
+        stream_signals(&params, Arc::new(YahooProvider::new()?), &mut sinks, &mut std::io::stdout()).await?;
+        Ok(())
+    }
+
+
+    #[tokio::test]
+    async fn it_writes_rows_in_symbol_order() {
+        let symbols: Vec<String> = ["MSFT", "AAPL", "UBER", "GOOG"].iter().map(|s| s.to_string()).collect();
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        // the first symbols take the longest so completion order is the reverse of the supplied order
+        let rows = collect_rows(&symbols, &start, &SignalSettings::default(), false, |symbol| async move {
+            let delay = match symbol.as_str() { "MSFT" => 40, "AAPL" => 30, "UBER" => 20, _ => 10 };
+            tokio_time::sleep(tokio_time::Duration::from_millis(delay)).await;
+            Ok(vec![1.0, 2.0, 3.0])
+        }).await;
+        let ordered: Vec<&str> = rows.values().flatten().map(|row| row.symbol.as_str()).collect();
+        assert_eq!(ordered, vec!["MSFT", "AAPL", "UBER", "GOOG"]);
+    }
+
+    #[tokio::test]
+    async fn it_counts_failed_tasks_as_failed_symbols() {
+        let symbols: Vec<String> = ["AAPL", "UBER", "MSFT"].iter().map(|s| s.to_string()).collect();
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let rows = collect_rows(&symbols, &start, &SignalSettings::default(), false, |symbol| async move {
+            if symbol == "UBER" {
+                panic!("the fetch of {} panicked", symbol);
+            }
+            Ok(vec![1.0, 2.0, 3.0])
+        }).await;
+        assert_eq!(rows.len(), 3);
+        assert!(rows[&0].is_ok() && rows[&2].is_ok());
+        assert!(matches!(&rows[&1], Err(FetchError::Internal(reason)) if reason.contains("panic")));
+    }
+
+    #[tokio::test]
+    async fn it_skips_symbols_that_time_out() {
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: vec![quote(2, 2.0), quote(1, 1.0)],
+            slow: vec!["UBER".to_string()],
+            delay: tokio_time::Duration::from_secs(5),
+        });
+        let symbols: Vec<String> = ["AAPL", "UBER", "MSFT"].iter().map(|s| s.to_string()).collect();
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let end: DateTime<Utc> = DateTime::from_str("2020-1-31 23:59:59.99 UTC").unwrap();
+        let timeout = tokio_time::Duration::from_millis(50);
+        let options = FetchOptions { timeout, ..FetchOptions::default() };
+
+        let err = fetch_closing_data(provider.as_ref(), "UBER", &start, &end, &options).await.unwrap_err();
+        assert_eq!(err, FetchError::TimedOut);
+
+        let rows = collect_rows(&symbols, &start, &SignalSettings::default(), false, |symbol| {
+            let provider = provider.clone();
+            async move { fetch_closing_data(provider.as_ref(), &symbol, &start, &end, &options).await }
+        }).await;
+        let fetched: Vec<&str> = rows.values().flatten().map(|row| row.symbol.as_str()).collect();
+        assert_eq!(fetched, vec!["AAPL", "MSFT"]);
+        assert_eq!(rows[&0].as_ref().unwrap().price, 2.0);
+        assert_eq!(rows[&1], Err(FetchError::TimedOut));
+    }
+
+    #[tokio::test]
+    async fn it_reports_and_fills_gaps() -> Result<(),Error> {
+        // Mon 1st, Tue 2nd, then nothing until Fri 5th and Mon 8th January 2024
+        let day = |n: u64| 1_704_067_200 + (n - 1) * 86_400;
+        let quotes = vec![quote(day(8), 4.0), quote(day(1), 1.0), quote(day(5), 3.0), quote(day(2), 2.0)];
+        assert_eq!(missing_trading_days(day(5), day(8)), 0);
+        assert_eq!(missing_trading_days(day(2), day(5)), 2);
+
+        let provider = MockProvider { quotes, slow: vec![], delay: tokio_time::Duration::ZERO };
+        let start: DateTime<Utc> = DateTime::from_str("2024-1-1 0:00:00.00 UTC").unwrap();
+        let end: DateTime<Utc> = DateTime::from_str("2024-1-31 0:00:00.00 UTC").unwrap();
+        let timeout = tokio_time::Duration::from_secs(1);
+        let options = FetchOptions { timeout, ..FetchOptions::default() };
+        let closes = fetch_closing_data(&provider, "AAPL", &start, &end, &options).await?;
+        assert_eq!(closes, vec![1.0, 2.0, 3.0, 4.0]);
+        let options = FetchOptions { fill_gaps: true, ..options };
+        let closes = fetch_closing_data(&provider, "AAPL", &start, &end, &options).await?;
+        assert_eq!(closes, vec![1.0, 2.0, 2.0, 2.0, 3.0, 4.0]);
+
+        let sorted: Vec<Quote> = [1, 2, 5, 8].iter().zip(1..).map(|(d, c)| quote(day(*d), c as f64)).collect();
+        let sorted = to_bars(&sorted, PriceSource::Adjclose);
+        let (bars, missing) = bars_with_gaps(&sorted, true);
+        assert_eq!(missing, 2);
+        assert_eq!(bars.iter().map(|bar| bar.timestamp).collect::<Vec<_>>(), [1, 2, 3, 4, 5, 8].map(day));
+        assert_eq!(bars_with_gaps(&sorted, false).0.len(), 4);
+        assert!(Params::from_opts(Opts::parse_from(["async_streams", "--fill-gaps"])).fetch.fill_gaps);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_resamples_before_calculating() -> Result<(),Error> {
+        // weekdays of 1st to 12th January 2024
+        let day = |n: u64| 1_704_067_200 + (n - 1) * 86_400;
+        let quotes = [1, 2, 3, 4, 5, 8, 9, 10, 11, 12].iter().map(|d| quote(day(*d), *d as f64)).collect();
+        let provider = MockProvider { quotes, slow: vec![], delay: tokio_time::Duration::ZERO };
+        let start: DateTime<Utc> = DateTime::from_str("2024-1-1 0:00:00.00 UTC").unwrap();
+        let end: DateTime<Utc> = DateTime::from_str("2024-1-31 0:00:00.00 UTC").unwrap();
+        let timeout = tokio_time::Duration::from_secs(1);
+        let closes = fetch_closing_data(&provider, "AAPL", &start, &end, &FetchOptions { timeout, resample: Some(Resample::Weekly), ..FetchOptions::default() }).await?;
+        assert_eq!(closes, vec![5.0, 12.0]);
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--resample", "monthly"]));
+        assert_eq!(params.fetch.resample, Some(Resample::Monthly));
+        assert!(Opts::try_parse_from(["async_streams", "--resample", "hourly"]).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_prints_nothing_when_quiet() -> Result<(),Error> {
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: vec![quote(1, 1.0), quote(2, 2.0)],
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        });
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL"]));
+        let (sink, mut console) = (MemorySink::default(), Vec::new());
+        stream_signals(&params, provider.clone(), &mut [sink.boxed()], &mut console).await?;
+        assert!(String::from_utf8(console).unwrap().starts_with(&StockRow::csv_header(&Column::DEFAULT)));
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL", "--quiet"]));
+        let (sink, mut console) = (MemorySink::default(), Vec::new());
+        stream_signals(&params, provider, &mut [sink.boxed()], &mut console).await?;
+        assert!(console.is_empty());
+        assert_eq!(sink.rows.lock().unwrap().len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_writes_signals_as_csv() -> Result<(),Error> {
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let rows = vec![
+            calculate_signals("AAPL", &start, &[10.0, 11.0, 12.0, 20.0], &SignalSettings::default()).await,
+            calculate_signals("MSFT", &start, &[4.0, 2.0, 3.0], &SignalSettings::default()).await,
+        ];
+        let mut writer = Vec::new();
+        write_signals(&mut CsvSink::new(&mut writer), &rows)?;
+        assert_eq!(
+            String::from_utf8(writer).unwrap(),
+            "period start,symbol,price,change %,min,max,sma,cum return %\n\
+             2020-01-01T00:00:00+00:00,AAPL,$20.00,100.00%,$10.00,$20.00,$14.33,100.00%\n\
+             2020-01-01T00:00:00+00:00,MSFT,$3.00,-25.00%,$2.00,$4.00,$3.00,-25.00%\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_writes_to_every_sink() -> Result<(),Error> {
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: vec![quote(1, 1.0), quote(2, 2.0)],
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        });
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT,UBER", "--quiet"]));
+        let (first, second) = (MemorySink::default(), MemorySink::default());
+        stream_signals(&params, provider, &mut [first.boxed(), second.boxed()], &mut std::io::sink()).await?;
+        for sink in [first, second] {
+            let symbols: Vec<String> = sink.rows.lock().unwrap().iter().map(|row| row.symbol.clone()).collect();
+            assert_eq!(symbols, vec!["AAPL", "MSFT", "UBER"]);
+            assert!(*sink.finished.lock().unwrap());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_sinks() {
+        let params = Params::from_opts(Opts::parse_from(["async_streams"]));
+        assert_eq!(params.sinks, vec![SinkSpec { path: "data.csv".to_string(), format: OutputFormat::Csv, symbol: None }]);
+        assert!(!params.quiet);
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--sink", "out/data.csv:csv", "--sink=-:JSON"]));
+        assert_eq!(params.sinks, vec![
+            SinkSpec { path: "out/data.csv".to_string(), format: OutputFormat::Csv, symbol: None },
+            SinkSpec { path: "-".to_string(), format: OutputFormat::Json, symbol: None },
+        ]);
+        assert!(params.quiet);
+
+        assert!(Opts::try_parse_from(["async_streams", "--sink", "data.csv"]).is_err());
+        assert!(Opts::try_parse_from(["async_streams", "--sink", "data.xml:xml"]).is_err());
+    }
+
+    #[test]
+    fn it_writes_json_lines() -> Result<(),Error> {
+        let row = StockRow {
+            period_start: "2020-01-01T00:00:00+00:00".to_string(),
+            symbol: "AAPL".to_string(),
+            price: 2.0,
+            pct_change: Some(100.0),
+            period_min: Some(1.0),
+            period_max: Some(2.0),
+            last_sma: Some(1.5),
+            cum_return: Some(1.0),
+            annualized_vol: None,
+            rsi: None,
+            rel_strength: None,
+            last_quote: None,
+            currency: None,
+        };
+        let mut writer = Vec::new();
+        write_signals(&mut JsonSink::new(&mut writer), &[row.clone(), row])?;
+        let lines: Vec<String> = String::from_utf8(writer).unwrap().lines().map(str::to_string).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"period_start":"2020-01-01T00:00:00+00:00","symbol":"AAPL","price":2.0,"pct_change":100.0,"period_min":1.0,"period_max":2.0,"last_sma":1.5,"cum_return":1.0,"annualized_vol":null,"rsi":null,"rel_strength":null,"last_quote":null}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn it_sorts_symbols() {
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "MSFT,AAPL,UBER", "--sort"]));
+        assert_eq!(params.symbols, vec!["AAPL", "MSFT", "UBER"]);
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "MSFT,AAPL,UBER"]));
+        assert_eq!(params.symbols, vec!["MSFT", "AAPL", "UBER"]);
+    }
+
+    #[test]
+    fn it_deduplicates_symbols() {
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "MSFT, AAPL,msft,UBER ,aapl,AAPL"]));
+        assert_eq!(params.symbols, vec!["MSFT", "AAPL", "UBER"]);
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "UBER,MSFT,uber", "--sort"]));
+        assert_eq!(params.symbols, vec!["MSFT", "UBER"]);
+    }
+
+    #[tokio::test]
+    async fn it_gets_latest_quote() -> Result<(),YahooError>{
+        let provider = yahoo::YahooConnector::new().unwrap();
+        // get the latest quotes in 1 minute intervals
+        let response = provider.get_latest_quotes("AAPL", "1d").await?;
+        // extract just the latest valid quote summery
+        // including timestamp,open,close,high,low,volume
+        let quote = response.last_quote().unwrap();
+        let time: OffsetDateTime =
+            OffsetDateTime::from(UNIX_EPOCH + Duration::from_secs(quote.timestamp));
+        println!("At {} quote price of Apple was {}", time, quote.close);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_gets_a_quote_range() -> Result<(),YahooError>{
+        let provider = yahoo::YahooConnector::new().unwrap();
+        let start = datetime!(2020-1-1 0:00:00.00 UTC);
+        let end = datetime!(2020-1-31 23:59:59.99 UTC);
+        // returns historic quotes with daily interval
+        let resp = provider.get_quote_history("AAPL", start, end).await?;
+        let quotes = resp.quotes().unwrap();
+        println!("Apple's quotes in January: {:?}", quotes);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_retrieves_daily_quotes_for_the_last_month() -> Result<(),YahooError>{
+        let provider = yahoo::YahooConnector::new().unwrap();
+        let response = provider.get_quote_range("AAPL", "1d", "1mo").await?;
+        let quotes = response.quotes().unwrap();
+        println!("Apple's quotes of the last month: {:?}", quotes);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_finds_all_matching_tickers() -> Result<(),YahooError>{
+        let provider = yahoo::YahooConnector::new().unwrap();
+        let resp = provider.search_ticker("S&P500").await?;
+
+        println!("All tickers found while searching for 'S&P500':");
+        let items = resp.quotes.iter();
+        items.for_each(|item| println!("{}", item.symbol));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_adds_cumulative_return_column() {
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let row = calculate_signals("AAPL", &start, &[10.0, 11.0, 12.0, 20.0], &SignalSettings::default()).await;
+        assert_eq!(row.cum_return, Some(1.0));
+        assert!(StockRow::csv_header(&Column::DEFAULT).ends_with(",cum return %"));
+        assert_eq!(row.to_csv_row(), "2020-01-01T00:00:00+00:00,AAPL,$20.00,100.00%,$10.00,$20.00,$14.33,100.00%");
+    }
+
+    #[tokio::test]
+    async fn it_writes_an_aligned_table() -> Result<(),Error> {
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--output-format", "table"]));
+        assert_eq!(params.sinks, vec![SinkSpec { path: "-".to_string(), format: OutputFormat::Table, symbol: None }]);
+        assert!(params.quiet);
+
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let rows = vec![
+            calculate_signals("AAPL", &start, &[10.0, 11.0, 12.0, 20.0], &SignalSettings::default()).await,
+            calculate_signals("GOOGL", &start, &[100.0, 90.0, 95.0], &SignalSettings::default()).await,
+        ];
+        let mut writer = Vec::new();
+        let columns = vec![Column::Symbol, Column::Price, Column::Change, Column::Sma];
+        write_signals(&mut TableSink::with_columns(&mut writer, columns), &rows)?;
+        let table = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines, vec![
+            "symbol |  price | change % |    sma",
+            "-------+--------+----------+-------",
+            "AAPL   | $20.00 |  100.00% | $14.33",
+            "GOOGL  | $95.00 |   -5.00% | $95.00",
+        ]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_converts_prices_to_another_currency() -> Result<(),Error> {
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let mut row = calculate_signals("AAPL", &start, &[10.0, 11.0, 12.0, 20.0], &SignalSettings::default()).await;
+        let unconverted = row.clone();
+        row.convert(0.5, "EUR");
+        assert_eq!((row.price, row.period_min, row.period_max), (10.0, Some(5.0), Some(10.0)));
+        assert!((row.last_sma.unwrap() - unconverted.last_sma.unwrap() * 0.5).abs() < 1e-12);
+        assert_eq!((row.pct_change, row.cum_return), (unconverted.pct_change, unconverted.cum_return));
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL", "--currency", "eur", "-q"]));
+        assert_eq!(params.currency.as_deref(), Some("EUR"));
+        assert_eq!(fx_symbol("EUR"), "USDEUR=X");
+        assert_eq!(Params::from_opts(Opts::parse_from(["async_streams", "--currency", "USD"])).currency, None);
+
+        // the mock serves the same series for the FX symbol, so the rate is its last close of 2
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: vec![quote(1, 1.0), quote(2, 2.0)],
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        });
+        let sink = MemorySink::default();
+        let summary = stream_signals(&params, provider, &mut [sink.boxed()], &mut Vec::new()).await?;
+        let converted = sink.rows.lock().unwrap()[0].clone();
+        assert_eq!(converted.price, 4.0);
+        assert_eq!(converted.cell(Column::Price, 2), "4.00 EUR");
+        assert_eq!(StockRow::from_csv_columns(&converted.to_csv_row(), &Column::DEFAULT).unwrap().price, 4.0);
+        let conversion = CurrencyConversion { currency: "EUR".to_string(), fx_symbol: "USDEUR=X".to_string(), rate: 2.0 };
+        assert_eq!(summary.conversion.as_ref(), Some(&conversion));
+        let metadata = serde_json::to_value(run_metadata(&params, Utc::now(), summary.conversion.as_ref()))?;
+        assert_eq!(metadata["conversion"], serde_json::json!({ "currency": "EUR", "fx_symbol": "USDEUR=X", "rate": 2.0 }));
+
+        // without a rate the prices are written unconverted
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: vec![quote(1, 1.0), quote(2, 2.0)],
+            slow: vec!["USDEUR=X".to_string()],
+            delay: tokio_time::Duration::from_secs(5),
+        });
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL", "--currency", "eur", "-q", "--timeout", "0"]));
+        let sink = MemorySink::default();
+        let summary = stream_signals(&params, provider, &mut [sink.boxed()], &mut Vec::new()).await?;
+        assert_eq!(sink.rows.lock().unwrap()[0].cell(Column::Price, 2), "$2.00");
+        assert_eq!(summary.conversion, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_formats_csv_with_precision() -> Result<(),Error> {
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let row = calculate_signals("PENNY", &start, &[0.0123, 0.0125, 0.0131], &SignalSettings::default()).await;
+        assert_eq!(row.to_csv_row(), "2020-01-01T00:00:00+00:00,PENNY,$0.01,6.50%,$0.01,$0.01,$0.01,6.50%");
+        assert_eq!(row.to_csv_row_with_precision(4), "2020-01-01T00:00:00+00:00,PENNY,$0.0131,6.5041%,$0.0123,$0.0131,$0.0126,6.5041%");
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--precision", "4"]));
+        assert_eq!(params.precision, 4);
+        let mut writer = Vec::new();
+        write_signals(&mut CsvSink::new(&mut writer).with_precision(params.precision), &[row])?;
+        let csv = String::from_utf8(writer).unwrap();
+        assert_eq!(csv.lines().nth(1), Some("2020-01-01T00:00:00+00:00,PENNY,$0.0131,6.5041%,$0.0123,$0.0131,$0.0126,6.5041%"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_adds_annualized_volatility_column() -> Result<(),Error> {
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let closes = [10.0, 11.0, 10.5, 12.0];
+        let row = calculate_signals("AAPL", &start, &closes, &SignalSettings::default()).await;
+        assert_eq!(row.annualized_vol, None);
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--annualized-vol"]));
+        assert_eq!(params.settings.columns.last(), Some(&Column::AnnualizedVol));
+        let row = calculate_signals("AAPL", &start, &closes, &params.settings).await;
+        assert!(row.annualized_vol.unwrap() > 0.0);
+
+        let mut writer = Vec::new();
+        write_signals(&mut CsvSink::with_columns(&mut writer, params.settings.columns), &[row])?;
+        let csv = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], format!("{},annualized vol %", StockRow::csv_header(&Column::DEFAULT)));
+        assert_eq!(lines[1].split(',').count(), 9);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_selects_columns() -> Result<(),Error> {
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--columns", "symbol,price,sma,annualized_vol"]));
+        assert_eq!(params.settings.columns, vec![Column::Symbol, Column::Price, Column::Sma, Column::AnnualizedVol]);
+
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let row = calculate_signals("AAPL", &start, &[10.0, 11.0, 12.0, 20.0], &params.settings).await;
+        // signals outside the selection aren't calculated
+        assert_eq!(row.period_max, None);
+
+        let mut writer = Vec::new();
+        write_signals(&mut CsvSink::with_columns(&mut writer, params.settings.columns.clone()), std::slice::from_ref(&row))?;
+        let csv = String::from_utf8(writer).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "symbol,price,sma,annualized vol %");
+        assert_eq!(lines[1].split(',').collect::<Vec<_>>(), vec!["AAPL", "$20.00", "$14.33", "314.09%"]);
+
+        let mut writer = Vec::new();
+        write_signals(&mut JsonSink::with_columns(&mut writer, vec![Column::Symbol, Column::Price]), &[row])?;
+        assert_eq!(String::from_utf8(writer).unwrap(), "{\"symbol\":\"AAPL\",\"price\":20.0}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_unknown_columns() {
+        let err = Opts::try_parse_from(["async_streams", "--columns", "symbol,macd"]).unwrap_err().to_string();
+        assert!(err.contains("unknown column 'macd'"));
+        assert!(err.contains("expected one of: period_start, symbol, price, change, min, max, sma, cum_return, annualized_vol, rsi, rel_strength, last_quote"));
+    }
+
+    #[tokio::test]
+    async fn it_round_trips_parquet() -> Result<(),Error> {
+        use arrow_array::{Float64Array, StringArray, TimestampMicrosecondArray};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--output-format", "parquet"]));
+        assert_eq!(params.sinks, vec![SinkSpec { path: "data.parquet".to_string(), format: OutputFormat::Parquet, symbol: None }]);
+
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let rows = vec![
+            calculate_signals("AAPL", &start, &[10.0, 11.0, 12.0, 20.0], &SignalSettings::default()).await,
+            calculate_signals("MSFT", &start, &[4.0, 2.0, 3.0], &SignalSettings::default()).await,
+        ];
+        let path = std::env::temp_dir().join(format!("async_streams_{}.parquet", std::process::id()));
+        let spec = SinkSpec { path: path.to_string_lossy().to_string(), format: OutputFormat::Parquet, symbol: None };
+        write_signals(spec.open(&Column::DEFAULT, StockRow::DEFAULT_PRECISION, false, DEFAULT_BUFFER_SIZE)?.as_mut(), &rows)?;
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path)?)?.build()?;
+        let batches = reader.collect::<Result<Vec<_>, _>>().map_err(Error::other)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), Column::DEFAULT.len());
+
+        let period_start = batch.column_by_name("period_start").unwrap().as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
+        assert_eq!(period_start.value(0), start.timestamp_micros());
+        let symbol = batch.column_by_name("symbol").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!((symbol.value(0), symbol.value(1)), ("AAPL", "MSFT"));
+        let price = batch.column_by_name("price").unwrap().as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!((price.value(0), price.value(1)), (20.0, 3.0));
+        let cum_return = batch.column_by_name("cum_return").unwrap().as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!((cum_return.value(0), cum_return.value(1)), (1.0, -0.25));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_upserts_rows_into_sqlite() -> Result<(),Error> {
+        let path = std::env::temp_dir().join(format!("async_streams_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT", "-f", "2020-01-01T00:00:00Z", "--quiet"]));
+        for close in [2.0, 3.0] {
+            let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+                quotes: vec![quote(1, 1.0), quote(2, close)],
+                slow: vec![],
+                delay: tokio_time::Duration::ZERO,
+            });
+            let mut sinks: Vec<Box<dyn OutputSink>> = vec![Box::new(SqliteSink::open(&path)?)];
+            stream_signals(&params, provider, &mut sinks, &mut std::io::sink()).await?;
+        }
+
+        let connection = rusqlite::Connection::open(&path).map_err(Error::other)?;
+        let counts: Vec<(String, i64, f64)> = connection
+            .prepare("SELECT symbol, COUNT(*), MAX(price) FROM signals GROUP BY symbol, period_start ORDER BY symbol")
+            .and_then(|mut query| query.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?.collect())
+            .map_err(Error::other)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(counts, vec![("AAPL".to_string(), 1, 3.0), ("MSFT".to_string(), 1, 3.0)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_migrates_an_existing_sqlite_table() -> Result<(),Error> {
+        let path = std::env::temp_dir().join(format!("async_streams_migrate_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        // the table as the first SqliteSink created it
+        rusqlite::Connection::open(&path)
+            .and_then(|connection| connection.execute_batch(
+                "CREATE TABLE signals (
+                    symbol TEXT NOT NULL,
+                    period_start TEXT NOT NULL,
+                    price REAL NOT NULL,
+                    pct_change REAL NOT NULL,
+                    period_min REAL NOT NULL,
+                    period_max REAL NOT NULL,
+                    last_sma REAL NOT NULL,
+                    cum_return REAL NOT NULL,
+                    annualized_vol REAL,
+                    PRIMARY KEY (symbol, period_start)
+                );
+                INSERT INTO signals VALUES ('MSFT', '2020-01-01T00:00:00+00:00', 2.0, 100.0, 1.0, 2.0, 1.5, 1.0, NULL);",
+            ))
+            .map_err(Error::other)?;
+
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: (0..20).map(|day| quote(day * 86_400, 10.0 + day as f64)).collect(),
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        });
+        // min, max and the rest left out, which the old table wouldn't take
+        let mut params = Params::from_opts(Opts::parse_from([
+            "async_streams", "-s", "AAPL", "-f", "2020-01-01T00:00:00Z", "--quiet", "--columns", "symbol,price,rsi,rel_strength",
+            "--rs-benchmark", "SPY",
+        ]));
+        load_benchmark(&mut params, provider.as_ref()).await;
+        let mut sinks: Vec<Box<dyn OutputSink>> = vec![Box::new(SqliteSink::open(&path)?)];
+        stream_signals(&params, provider, &mut sinks, &mut std::io::sink()).await?;
+        drop(sinks);
+
+        let connection = rusqlite::Connection::open(&path).map_err(Error::other)?;
+        let stored: (Option<f64>, Option<f64>, Option<f64>) = connection
+            .query_row("SELECT rsi, rel_strength, period_min FROM signals WHERE symbol = 'AAPL'", [], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(Error::other)?;
+        let kept: f64 = connection.query_row("SELECT last_sma FROM signals WHERE symbol = 'MSFT'", [], |row| row.get(0)).map_err(Error::other)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(kept, 1.5);
+        // the benchmark is the same series, so the symbol neither leads nor lags it
+        assert_eq!(stored, (Some(100.0), Some(1.0), None));
+        Ok(())
+    }
+
+    #[test]
+    fn it_merges_config_under_flags() {
+        let config = Config::parse(r#"
+            symbols = ["IBM", "INTC"]
+            sma_window = 10
+            risk_free_rate = 0.04
+            trading_days_per_year = 250
+        "#).unwrap();
+        assert_eq!(config.symbols, Some(vec!["IBM".to_string(), "INTC".to_string()]));
+        assert!(Config::parse("unknown = 1").is_err());
+
+        let path = std::env::temp_dir().join(format!("async_streams_{}.toml", std::process::id()));
+        std::fs::write(&path, "symbols = [\"IBM\", \"INTC\"]\nsma_window = 10\nrisk_free_rate = 0.04\ntrading_days_per_year = 250\n").unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--config", &path]));
+        assert_eq!(params.symbols, vec!["IBM", "INTC"]);
+        assert_eq!(params.settings.sma_window, 10);
+        assert_eq!(params.settings.risk_free_rate, 0.04);
+        assert_eq!(params.settings.trading_days_per_year, 250.0);
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--config", &path, "-s", "AAPL", "--sma-window", "5"]));
+        assert_eq!(params.symbols, vec!["AAPL"]);
+        assert_eq!(params.settings.sma_window, 5);
+        assert_eq!(params.settings.risk_free_rate, 0.04);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(Opts::try_parse_from(["async_streams", "--config", &path]).is_err());
+    }
+
+    ///
+    /// Serves the same daily series for every symbol, the quotes in the range requested, and records the start of each
+    /// request.
+    ///
+    struct RecordingProvider {
+        quotes: Vec<Quote>,
+        requests: Mutex<Vec<(String, DateTime<Utc>)>>,
+    }
+
+    impl RecordingProvider {
+        ///
+        /// A quote at the open on each of the first `days` days of January 2024, closing at the day of the month.
+        ///
+        fn daily(days: u32) -> Self {
+            let open = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 14, 30, 0).unwrap().timestamp() as u64;
+            Self { quotes: (1..=days).map(|d| quote(open(d), d as f64)).collect(), requests: Mutex::default() }
+        }
+    }
+
+    #[async_trait]
+    impl QuoteProvider for RecordingProvider {
+        async fn get_quote_history(&self, symbol: &str, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+            self.requests.lock().unwrap().push((symbol.to_string(), *start));
+            let range = start.timestamp() as u64..=end.timestamp() as u64;
+            Ok(self.quotes.iter().filter(|quote| range.contains(&quote.timestamp)).cloned().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn it_only_fetches_after_the_watermark() -> Result<(),Error> {
+        let path = std::env::temp_dir().join(format!("async_streams_incremental_{}.csv", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let _ = std::fs::remove_file(&path);
+        let open = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 14, 30, 0).unwrap();
+        let sink = format!("{}:csv", path);
+        let args = ["async_streams", "-s", "AAPL,MSFT", "--sink", &sink, "-q", "--incremental",
+            "--from", "2024-01-01T00:00:00Z", "--to", "2024-01-10T00:00:00Z"];
+        let mut params = Params::from_opts(Opts::parse_from(args));
+        assert!(params.settings.columns.contains(&Column::LastQuote));
+        let run = |params: &Params, provider: Arc<RecordingProvider>| {
+            let params = params.clone();
+            async move {
+                let mut sinks = vec![params.sinks[0].open(&params.settings.columns, params.precision, true, DEFAULT_BUFFER_SIZE)?];
+                stream_signals(&params, provider.clone(), &mut sinks, &mut Vec::new()).await?;
+                drop(sinks);
+                let mut requests = provider.requests.lock().unwrap().clone();
+                requests.sort();
+                Ok::<_, Error>(requests)
+            }
+        };
+
+        // the first run has the quotes up to the 4th
+        params.watermarks = read_watermarks(&params)?;
+        assert!(params.watermarks.is_empty());
+        run(&params, Arc::new(RecordingProvider::daily(4))).await?;
+        let csv = std::fs::read_to_string(&path)?;
+        assert!(csv.lines().nth(1).unwrap().ends_with(",2024-01-04T14:30:00+00:00"));
+
+        // by the second they run to the 7th, and only those after the 4th's are fetched
+        params.watermarks = read_watermarks(&params)?;
+        assert_eq!(params.watermarks["AAPL"], open(4) + TimeDelta::seconds(1));
+        let requests = run(&params, Arc::new(RecordingProvider::daily(7))).await?;
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|(_, start)| *start > open(4)));
+        let csv = std::fs::read_to_string(&path)?;
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines.iter().filter(|line| line.starts_with("period start,")).count(), 1);
+        // the new rows are over the 5th to the 7th alone: a cumulative return from 5 to 7
+        let row = StockRow::from_csv_columns(lines[3], &params.settings.columns).unwrap();
+        assert_eq!((row.symbol.as_str(), row.price, row.last_quote.as_deref()), ("AAPL", 7.0, Some("2024-01-07T14:30:00+00:00")));
+        assert_eq!(row.cum_return, Some(0.4));
+
+        // nothing left to fetch once the watermarks reach the end of the range
+        params.watermarks = read_watermarks(&params)?;
+        params.end = open(7);
+        let provider = Arc::new(RecordingProvider::daily(7));
+        stream_signals(&params, provider.clone(), &mut [], &mut Vec::new()).await?;
+        assert!(provider.requests.lock().unwrap().is_empty());
+
+        // rows written before the last quote was recorded pick up the day after their period start
+        assert_eq!(next_period_start("2024-01-03T00:00:00+00:00", None), Some(Utc.with_ymd_and_hms(2024, 1, 4, 0, 0, 0).unwrap()));
+        assert_eq!(next_period_start("2024-01-03T00:00:00+00:00", Some("2024-01-03T14:30:00-05:00")), Some(open(3) + TimeDelta::hours(5) + TimeDelta::seconds(1)));
+
+        std::fs::remove_file(&path)?;
+        assert!(SinkSpec { path: path.clone(), format: OutputFormat::Parquet, symbol: None }.open(&Column::DEFAULT, 2, true, DEFAULT_BUFFER_SIZE).is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn it_calculates_off_the_reactor_with_unchanged_results() {
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let symbols: Vec<String> = (0..16).map(|i| format!("SYM{}", i)).collect();
+        let series = |i: usize| -> Vec<f64> { (0..5_000).map(|n| 100.0 + ((n * (i + 1)) as f64).sin()).collect() };
+        let mut settings = SignalSettings::default();
+        settings.columns.push(Column::AnnualizedVol);
+
+        let rows = collect_rows(&symbols, &start, &settings, false, |symbol| {
+            let closes = series(symbol[3..].parse().unwrap());
+            async move { Ok(closes) }
+        }).await;
+        assert_eq!(rows.len(), symbols.len());
+        for (index, row) in rows {
+            assert_eq!(row.unwrap(), calculate_signals(&symbols[index], &start, &series(index), &settings).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn it_alerts_on_breached_bounds() -> Result<(),Error> {
+        let alert: Alert = "rsi>70".parse().unwrap();
+        assert_eq!(alert, Alert { column: Column::Rsi, comparator: alert::Comparator::Above, threshold: 70.0 });
+        assert_eq!("change<=-5".parse::<Alert>().unwrap().comparator, alert::Comparator::AtMost);
+        assert!("symbol>1".parse::<Alert>().is_err());
+        assert!("rsi=70".parse::<Alert>().is_err());
+        assert!("rsi>high".parse::<Alert>().is_err());
+
+        let args = ["async_streams", "-s", "AAPL,MSFT", "-q", "--alert", "rsi>70", "--alert", "price<1"];
+        let params = Params::from_opts(Opts::parse_from(args));
+        assert_eq!(params.settings.columns.last(), Some(&Column::Rsi));
+
+        // a steady climb: every change is a gain, so the RSI is pinned at 100
+        let quotes = (1..=20).map(|i| quote(i, 10.0 + i as f64)).collect();
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider { quotes, slow: vec![], delay: tokio_time::Duration::ZERO });
+        let sink = MemorySink::default();
+        let summary = stream_signals(&params, provider, &mut [sink.boxed()], &mut Vec::new()).await?;
+        assert_eq!(summary, StreamSummary { rows: 2, failed: vec![], breached: 2, telemetry: summary.telemetry.clone(), conversion: None });
+        let rows = sink.rows.lock().unwrap().clone();
+        assert_eq!(rows[0].rsi, Some(100.0));
+        assert_eq!(alert_messages(&params.alerts, &rows), vec![
+            "ALERT AAPL: rsi is 100.00, breaching rsi>70",
+            "ALERT MSFT: rsi is 100.00, breaching rsi>70",
+        ]);
+        assert_eq!(ALERT_EXIT_CODE, 2);
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL", "-q", "--alert", "rsi<30"]));
+        let quotes = (1..=20).map(|i| quote(i, 10.0 + i as f64)).collect();
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider { quotes, slow: vec![], delay: tokio_time::Duration::ZERO });
+        assert_eq!(stream_signals(&params, provider, &mut [], &mut Vec::new()).await?.breached, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_labels_the_period_start_in_a_timezone() {
+        let start: DateTime<Utc> = DateTime::from_str("2024-1-15 14:30:00.00 UTC").unwrap();
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--timezone", "Etc/GMT-2"]));
+        assert_eq!(params.settings.timezone, Some(chrono_tz::Etc::GMTMinus2));
+        let row = calculate_signals("AAPL", &start, &[1.0, 2.0], &params.settings).await;
+        assert_eq!(row.period_start, "2024-01-15T16:30:00+02:00");
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--timezone", "America/New_York"]));
+        let row = calculate_signals("AAPL", &start, &[1.0, 2.0], &params.settings).await;
+        assert_eq!(row.period_start, "2024-01-15T09:30:00-05:00");
+        assert_eq!(DateTime::parse_from_rfc3339(&row.period_start).unwrap().to_utc(), start);
+
+        let err = Opts::try_parse_from(["async_streams", "--timezone", "Mars/Olympus"]).unwrap_err().to_string();
+        assert!(err.contains("unknown timezone 'Mars/Olympus'"));
+    }
+
+    #[tokio::test]
+    async fn it_selects_the_close_and_drops_non_finite_quotes() -> Result<(),Error> {
+        let raw = |timestamp: u64, close: f64, adjclose: f64| Quote { timestamp, open: close, high: close, low: close, volume: 0, close, adjclose };
+        let quotes = vec![raw(1, 10.0, 9.5), raw(2, 11.0, f64::NAN), raw(3, 12.0, 11.5)];
+        let provider = MockProvider { quotes, slow: vec![], delay: tokio_time::Duration::ZERO };
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let end: DateTime<Utc> = DateTime::from_str("2020-1-31 0:00:00.00 UTC").unwrap();
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams"]));
+        assert_eq!(params.fetch.price_source, PriceSource::Adjclose);
+        assert_eq!(fetch_closing_data(&provider, "AAPL", &start, &end, &params.fetch).await?, vec![9.5, 11.5]);
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--use-adjusted", "false"]));
+        assert_eq!(params.fetch.price_source, PriceSource::Close);
+        assert_eq!(fetch_closing_data(&provider, "AAPL", &start, &end, &params.fetch).await?, vec![10.0, 11.0, 12.0]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_keeps_progress_out_of_the_output() -> Result<(),Error> {
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT,UBER"]));
+        assert_eq!(progress_bar(&params, true).length(), Some(3));
+        assert_eq!(progress_bar(&params, false).length(), None);
+        let quiet = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL", "-q"]));
+        assert_eq!(progress_bar(&quiet, true).length(), None);
+
+        let path = std::env::temp_dir().join(format!("async_streams_progress_{}.csv", std::process::id()));
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: vec![quote(1, 1.0), quote(2, 2.0)],
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        });
+        let mut sinks: Vec<Box<dyn OutputSink>> = vec![Box::new(CsvSink::new(std::fs::File::create(&path)?))];
+        let mut console = Vec::new();
+        stream_signals(&params, provider, &mut sinks, &mut console).await?;
+        drop(sinks);
+        let csv = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.lines().skip(1).all(|line| line.split(',').count() == 8));
+        assert_eq!(String::from_utf8(console).unwrap(), csv);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_reports_full_series_per_symbol() -> Result<(),Error> {
+        let params = Params::from_opts(Opts::parse_from([
+            "async_streams", "-s", "AAPL,MSFT", "--output-format", "report", "--sma-window", "3",
+        ]));
+        assert_eq!(params.sinks, vec![SinkSpec { path: "data.json".to_string(), format: OutputFormat::Report, symbol: None }]);
+        let closes = [10.0, 11.0, 12.0, 9.0, 10.0, 13.0, 12.0, 14.0, 15.0, 13.0, 16.0, 17.0, 15.0, 18.0, 19.0, 20.0, 18.0];
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: closes.iter().enumerate().map(|(day, close)| quote(day as u64 * 86_400, *close)).collect(),
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        });
+        let report = build_report(&params, provider).await;
+        let json = serde_json::to_value(&report)?;
+
+        assert_eq!(json["start"], params.start.to_rfc3339());
+        assert_eq!(json["end"], params.end.to_rfc3339());
+        assert_eq!(json["parameters"], serde_json::json!({"window": 3, "rsi_period": 14}));
+        let symbols = json["symbols"].as_object().unwrap();
+        assert_eq!(symbols.keys().collect::<Vec<_>>(), ["AAPL", "MSFT"]);
+        for symbol in symbols.values() {
+            let len = |series: &str| symbol[series].as_array().unwrap().len();
+            assert_eq!(len("closes"), closes.len());
+            assert_eq!(len("sma"), closes.len() - 2);
+            assert_eq!(len("ema"), len("sma"));
+            assert_eq!(len("rsi"), closes.len() - 14);
+            assert_eq!(symbol["min"], 9.0);
+            assert_eq!(symbol["max"], 20.0);
+            assert!((symbol["max_drawdown"].as_f64().unwrap() - 0.25).abs() < 1e-12);
+        }
+        assert_eq!(report::max_drawdown(&[1.0, 2.0, 3.0]), 0.0);
+        assert!(params.sinks[0].open(&[], 2, false, DEFAULT_BUFFER_SIZE).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_limits_the_closes_per_symbol() -> Result<(),Error> {
+        let provider = MockProvider {
+            quotes: (1..=10).map(|day| quote(day * 86_400, day as f64)).collect(),
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        };
+        let (start, end) = (Utc::now(), Utc::now());
+        let limited = |limit_rows: Option<usize>| FetchOptions { limit_rows, ..FetchOptions::default() };
+        let closes = fetch_closing_data(&provider, "AAPL", &start, &end, &limited(Some(3))).await?;
+        assert_eq!(closes, vec![8.0, 9.0, 10.0]);
+        assert_eq!(fetch_closing_data(&provider, "AAPL", &start, &end, &limited(Some(50))).await?.len(), 10);
+        assert_eq!(fetch_closing_data(&provider, "AAPL", &start, &end, &limited(Some(0))).await?.len(), 0);
+        assert_eq!(fetch_closing_data(&provider, "AAPL", &start, &end, &limited(None)).await?.len(), 10);
 
-#[derive(Debug, Clone)]
-struct Params {
-    symbols: Vec<String>,
-    start: DateTime<Utc>,
-    end: DateTime<Utc>,
-}
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--limit-rows", "3"]));
+        assert_eq!(params.fetch.limit_rows, Some(3));
+        Ok(())
+    }
 
-impl Default for Params {
-    fn default() -> Self {
-        let opts = Opts::parse();
-        let default_symbols: Vec<String> = vec!["AAPL".to_string(), "MSFT".to_string(), "UBER".to_string(), "GOOG".to_string()];
-        let symbols: Vec<String> = match opts.symbols {
-            Some(symbols) => symbols.split(",").map(|v| v.trim().to_string()).collect(),
-            None => default_symbols.into_iter().map(|v| v.to_string()).collect(),
-        };
-        let default_start: DateTime<Utc> = Utc::now() - TimeDelta::weeks(2);
-        let start: DateTime<Utc> = match opts.from {
-            Some(from) => from.parse().unwrap_or(default_start),
-            None => default_start
-        };
-        let default_end: DateTime<Utc> = Utc::now();
-        let end = match opts.to {
-            Some(to) => to.parse().unwrap_or(default_end),
-            None => default_end,
-        };
+    #[tokio::test]
+    async fn it_warns_when_no_symbol_has_data() -> Result<(),Error> {
+        let args = ["async_streams", "-s", "AAPL,MSFT", "-f", "2024-01-06T00:00:00Z", "-t", "2024-01-07T23:59:59Z", "--strict"];
+        let params = Params::from_opts(Opts::parse_from(args));
+        assert!(params.strict);
+        // a weekend: nothing traded
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider { quotes: vec![], slow: vec![], delay: tokio_time::Duration::ZERO });
+        let (sink, mut console) = (MemorySink::default(), Vec::new());
+        let summary = stream_signals(&params, provider, &mut [sink.boxed()], &mut console).await?;
+        assert_eq!(summary.rows, 0);
+        assert_eq!(summary.failed, vec![("AAPL".to_string(), FetchError::Empty), ("MSFT".to_string(), FetchError::Empty)]);
+        assert!(sink.rows.lock().unwrap().is_empty());
+        assert_eq!(String::from_utf8(console).unwrap().trim_end(), StockRow::csv_header(&Column::DEFAULT));
 
-        match start.cmp(&end) {
-            Ordering::Greater => {
-                Self {
-                    symbols,
-                    start: end,
-                    end: start
-                }
-            },
-            _ => {
-                Self {
-                    symbols,
-                    start,
-                    end
-                }
-            },
+        let warning = empty_result_warning(&params);
+        assert!(warning.starts_with("warning: no rows were written, none of AAPL, MSFT had quotes between 2024-01-06 and 2024-01-07."));
+        assert!(warning.contains("weekend or holiday"));
+        assert!(warning.contains("symbols may not exist"));
+        assert_eq!(summary.exit_code(true), FAILED_EXIT_CODE);
+        assert_eq!(summary.exit_code(false), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_reads_quotes_from_a_local_csv() -> Result<(),Error> {
+        let dir = std::env::temp_dir().join(format!("async_streams_input_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let closes = [10.0, 11.0, 10.5, 12.0, 12.5, 11.5, 13.0];
+        let days = 1..=closes.len() as u32;
+        let csv = std::iter::once("Date,Open,High,Low,Close,Adj Close,Volume".to_string())
+            .chain(days.clone().zip(closes).map(|(day, close)| format!("2024-01-{:02},{},{},{},{},{},1000", day, close, close, close, close, close)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(dir.join("AAPL.csv"), csv)?;
+        // just the dates and closes, with a quote outside the range
+        let csv = std::iter::once("date,close".to_string())
+            .chain(days.zip(closes).map(|(day, close)| format!("2024-01-{:02}T00:00:00Z,{}", day, close)))
+            .chain(std::iter::once("2024-03-01,99.0".to_string()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(dir.join("MSFT.csv"), csv)?;
+
+        let template = format!("{}/{{symbol}}.csv", dir.display());
+        let args = ["async_streams", "-s", "AAPL,MSFT", "-q", "-f", "2024-01-01T00:00:00Z", "-t", "2024-01-31T00:00:00Z", "--input-csv", &template];
+        let params = Params::from_opts(Opts::parse_from(args));
+        assert_eq!(params.input_csv.as_deref(), Some(template.as_str()));
+        let csv_sink = MemorySink::default();
+        stream_signals(&params, Arc::new(CsvProvider::new(&template)), &mut [csv_sink.boxed()], &mut Vec::new()).await?;
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().timestamp() as u64;
+        let quotes = closes.iter().enumerate().map(|(day, close)| quote(start + day as u64 * 86_400, *close)).collect();
+        let mock_sink = MemorySink::default();
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider { quotes, slow: vec![], delay: tokio_time::Duration::ZERO });
+        stream_signals(&params, provider, &mut [mock_sink.boxed()], &mut Vec::new()).await?;
+
+        let rows = csv_sink.rows.lock().unwrap().clone();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows, *mock_sink.rows.lock().unwrap());
+        assert_eq!(rows[1].price, 13.0);
+
+        let provider = CsvProvider::new(&template);
+        let (from, to) = (params.start, params.end);
+        assert_eq!(provider.get_quote_history("UBER", &from, &to).await, Err(FetchError::NotFound));
+        std::fs::write(dir.join("UBER.csv"), "date,close\n2024-01-02,abc")?;
+        assert!(matches!(provider.get_quote_history("UBER", &from, &to).await, Err(FetchError::Decode(_))));
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_previews_the_head_or_tail_of_the_rows() -> Result<(),Error> {
+        let provider: Arc<dyn QuoteProvider> = Arc::new(MockProvider {
+            quotes: vec![quote(1, 1.0), quote(2, 2.0)],
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        });
+        for (flag, expected) in [("--head", ["AAPL", "MSFT"]), ("--tail", ["UBER", "GOOG"])] {
+            let args = ["async_streams", "-s", "AAPL,MSFT,UBER,GOOG", "--columns", "symbol,price", flag, "2"];
+            let params = Params::from_opts(Opts::parse_from(args));
+            let (sink, mut console) = (MemorySink::default(), Vec::new());
+            stream_signals(&params, provider.clone(), &mut [sink.boxed()], &mut console).await?;
+            assert_eq!(sink.rows.lock().unwrap().len(), 4);
+            let preview = String::from_utf8(console).unwrap();
+            assert_eq!(preview.lines().collect::<Vec<_>>(), vec![
+                "symbol | price".to_string(),
+                "-------+------".to_string(),
+                format!("{}   | $2.00", expected[0]),
+                format!("{}   | $2.00", expected[1]),
+                "(2 of 4 rows)".to_string(),
+            ]);
         }
+        assert_eq!(Preview::Tail(10).select(&[]).len(), 0);
+        assert!(Opts::try_parse_from(["async_streams", "--head", "1", "--tail", "1"]).is_err());
+        Ok(())
     }
-}
 
-///
-/// Retrieve data from a data source and extract the closing prices. Errors during download are mapped onto io::Errors as InvalidData.
-///
-async fn fetch_closing_data(
-    symbol: &str,
-    start: &DateTime<Utc>,
-    end: &DateTime<Utc>,
-) -> std::io::Result<Vec<f64>> {
-    let provider = yahoo::YahooConnector::new()
-        .map_err(|_| Error::from(ErrorKind::ConnectionRefused))?;
-    // incompatibility between chron and time crates
-    let start = OffsetDateTime::from_unix_timestamp(start.timestamp()).unwrap();
-    let end = OffsetDateTime::from_unix_timestamp(end.timestamp()).unwrap();
-    let resp = provider.get_quote_history(symbol, start, end).await
-        .map_err(|_| Error::from(ErrorKind::InvalidData))?;
-    let mut quotes = resp.quotes()
-        .map_err(|_| Error::from(ErrorKind::InvalidData))?;
-    if !quotes.is_empty() {
-        quotes.sort_by_cached_key(|k| k.timestamp);
-        Ok(quotes.iter().map(|q| q.adjclose).collect())
-    } else {
-        Ok(vec![])
+    ///
+    /// Fails for the listed symbols and serves `quotes` for the rest.
+    ///
+    struct FailingProvider {
+        quotes: Vec<Quote>,
+        failing: Vec<(String, FetchError)>,
     }
-}
 
-async fn calculate_signals(symbol: &str, start: &DateTime<Utc>, closes: &Vec<f64>) -> Signals {
-    let signal = MaxPrice {};
-    let period_max = signal.calculate(closes).await.unwrap_or(0.0);
-    let signal = MinPrice {};
-    let period_min = signal.calculate(closes).await.unwrap_or(0.0);
-    let signal = WindowedSMA::new(3);
-    let sma = signal.calculate(closes).await.unwrap_or(vec![]);
-    let signal = PriceDifference {};
-    let price_diff = signal.calculate(closes).await.unwrap_or((0.0, 0.0));
-    let pct_change = price_diff.1 * 100.0;
-    let last_price = *closes.last().unwrap_or(&0.0);
-    let last_sma = *sma.last().unwrap_or(&0.0);
-    let date = start.to_rfc3339();
-
-    let result = (date,
-                  symbol.to_string(),
-                  last_price,
-                  pct_change,
-                  period_min,
-                  period_max,
-                  last_sma);
-
-    result
-}
-
-async fn stream_signals(symbols: &Vec<String>, start: &DateTime<Utc>, end: &DateTime<Utc>) -> std::io::Result<()> {
-    let file = fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open("data.csv").await?;
-    let mut stream = BufWriter::new(file);
-    let header = "period start,symbol,price,change %,min,max,30d avg\n";
-    print!("{}", &header);
-    stream.write(header.as_bytes()).await?;
-    for symbol in symbols.iter() {
-        let closes = fetch_closing_data(&symbol, &start, &end).await;
-        match closes {
-            Ok(closes) => {
-                if !closes.is_empty() {
-                    let data = calculate_signals(symbol, &start, &closes).await;
-                    let row = format!("{},{},${:.2},{:.2}%,${:.2},${:.2},${:.2}\n", data.0, data.1, data.2, data.3, data.4, data.5, data.6);
-                    print!("{}", &row);
-                    stream.write(row.as_bytes()).await?;
-                }
+    #[async_trait]
+    impl QuoteProvider for FailingProvider {
+        async fn get_quote_history(&self, symbol: &str, _start: &DateTime<Utc>, _end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+            match self.failing.iter().find(|(failing, _)| failing == symbol) {
+                Some((_, e)) => Err(e.clone()),
+                None => Ok(self.quotes.clone()),
             }
-            Err(_) => eprintln!("\n{} data not found", &symbol),
         }
     }
-    stream.flush().await
-}
 
-async fn get_sp500() -> Result<Vec<String>, Error> {
-    let mut file = fs::File::open("sp500.txt").await?;
-    let mut buffer = String::new();
-    file.read_to_string(&mut buffer).await?;
-    let data = buffer.split(",").into_iter().map(|v| v.trim().to_string()).collect();
-    Ok(data)
-}
+    #[tokio::test]
+    async fn it_writes_the_good_symbols_when_others_fail() -> Result<(),Error> {
+        let failing = vec![
+            ("MSFT".to_string(), FetchError::NotFound),
+            ("GOOG".to_string(), FetchError::Network("connection reset".to_string())),
+        ];
+        let provider: Arc<dyn QuoteProvider> = Arc::new(FailingProvider { quotes: vec![quote(1, 1.0), quote(2, 2.0)], failing: failing.clone() });
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT,UBER,GOOG", "-q"]));
+        let sink = MemorySink::default();
+        let summary = stream_signals(&params, provider, &mut [sink.boxed()], &mut Vec::new()).await?;
+        let written: Vec<String> = sink.rows.lock().unwrap().iter().map(|row| row.symbol.clone()).collect();
+        assert_eq!(written, vec!["AAPL", "UBER"]);
+        assert_eq!(summary, StreamSummary { rows: 2, failed: failing, breached: 0, telemetry: summary.telemetry.clone(), conversion: None });
+        Ok(())
+    }
 
-#[async_recursion(Sync)]
-async fn print_signal_row(symbol: &'static str, start: DateTime<Utc>, end: DateTime<Utc>, attempt: u8) -> () {
-    let closes = fetch_closing_data(symbol, &start, &end).await;
-    if let Ok(closes) = closes {
-        if !closes.is_empty() {
-            let data = calculate_signals(symbol, &start, &closes).await;
-            let row = format!("{},{},${:.2},{:.2}%,${:.2},${:.2},${:.2}", data.0, data.1, data.2, data.3, data.4, data.5, data.6);
-            println!("{}", row);
+    ///
+    /// Serves a different series for each symbol.
+    ///
+    struct SeriesProvider {
+        series: HashMap<String, Vec<Quote>>,
+    }
+
+    #[async_trait]
+    impl QuoteProvider for SeriesProvider {
+        async fn get_quote_history(&self, symbol: &str, _start: &DateTime<Utc>, _end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+            Ok(self.series.get(symbol).cloned().unwrap_or_default())
         }
-    } else {
-        if attempt < 5 {
-            task::spawn(print_signal_row(symbol, start, end, attempt + 1));
-        } else {
-            eprintln!("{}: fetch error", symbol);
+    }
+
+    #[tokio::test]
+    async fn it_writes_a_correlation_matrix() -> Result<(),Error> {
+        let day = |n: u64| n * 86_400;
+        let up = [10.0, 11.0, 10.5, 12.0, 11.0, 13.0];
+        let series: HashMap<String, Vec<Quote>> = [
+            ("AAPL", up.iter().enumerate().map(|(n, close)| quote(day(n as u64), *close)).collect::<Vec<_>>()),
+            // twice the price moves the same way, so the returns are perfectly correlated
+            ("MSFT", up.iter().enumerate().map(|(n, close)| quote(day(n as u64), close * 2.0)).collect()),
+            ("UBER", [5.0, 4.0, 6.0, 5.5, 7.0, 6.0].iter().enumerate().map(|(n, close)| quote(day(n as u64), *close)).collect()),
+            // only two days in common with the others
+            ("GOOG", vec![quote(day(4), 1.0), quote(day(5), 2.0), quote(day(9), 3.0), quote(day(10), 4.0), quote(day(11), 5.0)]),
+        ].into_iter().map(|(symbol, quotes)| (symbol.to_string(), quotes)).collect();
+        let provider: Arc<dyn QuoteProvider> = Arc::new(SeriesProvider { series });
+
+        let path = std::env::temp_dir().join(format!("async_streams_corr_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT,UBER,GOOG", "--corr-matrix", &path]));
+        assert_eq!(params.corr_matrix.as_deref(), Some(path.as_str()));
+        let matrix = write_correlation_matrix(&params, provider, &path).await?;
+        assert_eq!(matrix.symbols, vec!["AAPL", "MSFT", "UBER"]);
+        assert_eq!(matrix.observations, 5);
+        for i in 0..3 {
+            assert!((matrix.values[i][i] - 1.0).abs() < 1e-12);
+            for j in 0..3 {
+                assert!((matrix.values[i][j] - matrix.values[j][i]).abs() < 1e-12);
+                assert!(matrix.values[i][j].abs() <= 1.0 + 1e-12);
+            }
         }
+        assert!((matrix.values[0][1] - 1.0).abs() < 1e-12);
+        assert!(matrix.values[0][2] < 0.0);
+
+        let csv = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], ",AAPL,MSFT,UBER");
+        assert!(lines[1].starts_with("AAPL,1.0000,1.0000,-"));
+        assert_eq!(lines.len(), 4);
+        Ok(())
     }
-}
 
-#[tokio::main]
-async fn main() -> std::io::Result<()> {
-    let symbols = Box::leak(Box::new(get_sp500().await?));
-    let mut stream = IntervalStream::new(tokio_time::interval(tokio_time::Duration::from_secs(30)));
+    #[test]
+    fn it_chooses_the_exit_code_from_the_summary() {
+        let failed = |symbols: &[&str], e: FetchError| symbols.iter().map(|symbol| (symbol.to_string(), e.clone())).collect::<Vec<_>>();
+        let network = FetchError::Network("connection reset".to_string());
 
-    while let Some(_) = stream.next().await {
-        let end: DateTime<Utc> = Utc::now();
-        let start = end - TimeDelta::days(1);
-        for symbol in symbols.iter() {
-            task::spawn(print_signal_row(symbol, start, end, 0));
+        let success = StreamSummary { rows: 3, failed: vec![], breached: 0, ..Default::default() };
+        assert_eq!(success.exit_code(false), 0);
+        let breached = StreamSummary { rows: 3, failed: vec![], breached: 1, ..Default::default() };
+        assert_eq!(breached.exit_code(false), ALERT_EXIT_CODE);
+        let all_failed = StreamSummary { rows: 0, failed: failed(&["AAPL", "MSFT"], network.clone()), breached: 0, ..Default::default() };
+        assert_eq!(all_failed.exit_code(false), FAILED_EXIT_CODE);
+        let partial = StreamSummary { rows: 1, failed: failed(&["MSFT"], FetchError::TimedOut), breached: 0, ..Default::default() };
+        assert_eq!(partial.exit_code(false), PARTIAL_EXIT_CODE);
+        let partial_and_breached = StreamSummary { rows: 1, failed: failed(&["MSFT"], network), breached: 2, ..Default::default() };
+        assert_eq!(partial_and_breached.exit_code(false), ALERT_EXIT_CODE);
+
+        // a symbol without any quotes in range is only a failure when strict
+        let empty = StreamSummary { rows: 2, failed: failed(&["MSFT"], FetchError::Empty), breached: 0, ..Default::default() };
+        assert_eq!(empty.exit_code(false), 0);
+        assert_eq!(empty.exit_code(true), PARTIAL_EXIT_CODE);
+        // nothing fetched in an incremental run that was already up to date
+        assert_eq!(StreamSummary::default().exit_code(true), 0);
+
+        assert_eq!([USAGE_EXIT_CODE, ALERT_EXIT_CODE, FAILED_EXIT_CODE, PARTIAL_EXIT_CODE], [1, 2, 3, 4]);
+        assert!(Opts::try_parse_from(["async_streams", "--output-format", "xml"]).unwrap_err().use_stderr());
+    }
+
+    #[tokio::test]
+    async fn it_normalizes_the_closes_to_100() -> Result<(),Error> {
+        let provider = MockProvider {
+            quotes: [40.0, 50.0, 30.0, 44.0].iter().enumerate().map(|(day, close)| quote(day as u64 * 86_400, *close)).collect(),
+            slow: vec![],
+            delay: tokio_time::Duration::ZERO,
+        };
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--normalize"]));
+        assert!(params.normalize);
+        let (start, end) = (params.start, params.end);
+        let bars = rebased("AAPL", fetch_bars(&provider, "AAPL", &start, &end, &params.fetch).await?, params.normalize);
+        let closes: Vec<f64> = bars.iter().map(|bar| bar.close).collect();
+        assert_eq!(closes[0], 100.0);
+        assert_eq!(closes, vec![100.0, 125.0, 75.0, 110.0]);
+        let raw = fetch_closing_data(&provider, "AAPL", &start, &end, &FetchOptions::default()).await?;
+        let ratios = |series: &[f64]| series.windows(2).map(|pair| pair[1] / pair[0]).collect::<Vec<_>>();
+        assert!(ratios(&closes).iter().zip(ratios(&raw)).all(|(a, b)| (a - b).abs() < 1e-12));
+
+        // a leading zero is skipped for the base and an all zero series left alone
+        let bar = |close: f64| Ohlcv { timestamp: 0, open: close, high: close, low: close, close, volume: 0 };
+        let rebased = normalized(&[bar(0.0), bar(20.0), bar(30.0)]).unwrap();
+        assert_eq!(rebased.iter().map(|bar| bar.close).collect::<Vec<_>>(), vec![0.0, 100.0, 150.0]);
+        assert_eq!(normalized(&[bar(0.0), bar(0.0)]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn it_adapts_the_default_range_to_the_interval() {
+        let lookback = |args: &[&str]| {
+            let params = Params::from_opts(Opts::parse_from(["async_streams"].iter().chain(args)));
+            params.end - params.start
+        };
+        assert_eq!(lookback(&[]), TimeDelta::weeks(2));
+        assert_eq!(lookback(&["--interval", "1d"]), TimeDelta::weeks(2));
+        assert_eq!(lookback(&["--interval", "1wk"]), TimeDelta::weeks(52));
+        assert_eq!(lookback(&["--interval", "1mo"]), TimeDelta::days(5 * 365));
+        assert_eq!(lookback(&["--interval", "1m"]), TimeDelta::days(1));
+        // resampled daily bars need as long a range as bars fetched at that period
+        assert_eq!(lookback(&["--resample", "weekly"]), TimeDelta::weeks(52));
+        assert_eq!(lookback(&["--interval", "1mo", "--resample", "weekly"]), TimeDelta::days(5 * 365));
+        // an explicit start is kept
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--interval", "1wk", "-f", "2024-01-01T00:00:00Z"]));
+        assert_eq!(params.start, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(params.fetch.interval, Interval::OneWeek);
+
+        assert_eq!("15M".parse::<Interval>(), Ok(Interval::FifteenMinutes));
+        assert!("2d".parse::<Interval>().unwrap_err().ends_with("1m, 5m, 15m, 30m, 1h, 1d, 1wk, 1mo"));
+        assert!(Interval::OneHour.is_intraday() && !Interval::OneWeek.is_intraday());
+    }
+
+    #[test]
+    fn it_writes_a_sample_config() -> Result<(),Error> {
+        let dir = std::env::temp_dir().join(format!("async_streams_config_init_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("async_streams.toml").to_string_lossy().to_string();
+        let opts = Opts::parse_from(["async_streams", "config-init", &path]);
+        let command = opts.command.unwrap();
+        assert_eq!(command, Command::ConfigInit { path: path.clone(), force: false });
+        command.run()?;
+
+        let sample = std::fs::read_to_string(&path)?;
+        assert!(sample.lines().filter(|line| !line.is_empty()).all(|line| line.starts_with('#') || line.contains(" = ")));
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config, default_config());
+        // loading the sample changes nothing
+        let defaults = Params::from_opts(Opts::parse_from(["async_streams"]));
+        let loaded = Params::from_opts(Opts::parse_from(["async_streams", "--config", &path]));
+        assert_eq!((loaded.symbols, loaded.settings), (defaults.symbols, defaults.settings));
+
+        std::fs::write(&path, "sma_window = 9\n")?;
+        assert!(command.run().unwrap_err().to_string().contains("already exists, pass --force"));
+        assert_eq!(std::fs::read_to_string(&path)?, "sma_window = 9\n");
+        Opts::parse_from(["async_streams", "config-init", &path, "--force"]).command.unwrap().run()?;
+        assert_eq!(Config::load(&path).unwrap(), default_config());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn it_widens_an_empty_range_by_a_day() {
+        let day = "2024-03-15T00:00:00Z";
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-f", day, "-t", day]));
+        assert_eq!(params.start, Utc.with_ymd_and_hms(2024, 3, 15, 0, 0, 0).unwrap());
+        assert_eq!(params.end, Utc.with_ymd_and_hms(2024, 3, 16, 0, 0, 0).unwrap());
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-f", "2024-03-16T00:00:00Z", "-t", day]));
+        assert_eq!(params.end - params.start, TimeDelta::days(1));
+        assert_eq!(params.start, Utc.with_ymd_and_hms(2024, 3, 15, 0, 0, 0).unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_stops_at_the_first_failure_when_failing_fast() -> Result<(),Error> {
+        // the failure is immediate while the other symbols are still being fetched
+        let provider = |slow: Vec<String>| -> Arc<dyn QuoteProvider> {
+            Arc::new(FailingSlowProvider {
+                inner: MockProvider { quotes: vec![quote(1, 1.0), quote(2, 2.0)], slow, delay: tokio_time::Duration::from_millis(200) },
+                failing: "AAPL".to_string(),
+            })
+        };
+        let slow = vec!["MSFT".to_string(), "UBER".to_string()];
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT,UBER", "-q", "--fail-fast"]));
+        assert!(params.fail_fast);
+        let sink = MemorySink::default();
+        let started = std::time::Instant::now();
+        let summary = stream_signals(&params, provider(slow.clone()), &mut [sink.boxed()], &mut Vec::new()).await?;
+        assert!(started.elapsed() < Duration::from_millis(200));
+        assert_eq!(summary.failed, vec![("AAPL".to_string(), FetchError::NotFound)]);
+        assert_eq!(summary.rows, 0);
+        assert_eq!(summary.exit_code(params.strict), FAILED_EXIT_CODE);
+        assert!(sink.rows.lock().unwrap().is_empty());
+
+        // by default the other symbols are still written
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT,UBER", "-q"]));
+        let sink = MemorySink::default();
+        let summary = stream_signals(&params, provider(slow), &mut [sink.boxed()], &mut Vec::new()).await?;
+        assert_eq!(summary.rows, 2);
+        assert_eq!(sink.rows.lock().unwrap().len(), 2);
+        Ok(())
+    }
+
+    struct FailingSlowProvider {
+        inner: MockProvider,
+        failing: String,
+    }
+
+    #[async_trait]
+    impl QuoteProvider for FailingSlowProvider {
+        async fn get_quote_history(&self, symbol: &str, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+            if symbol == self.failing {
+                return Err(FetchError::NotFound);
+            }
+            self.inner.get_quote_history(symbol, start, end).await
         }
     }
 
-    Ok(())
-}
+    #[test]
+    fn it_jitters_requests_within_the_bound() {
+        use rand::SeedableRng;
 
-async fn old_main() -> std::io::Result<()> {
-    println!();
-    let params = Params::default();
-    stream_signals(&params.symbols, &params.start, &params.end).await
-}
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--jitter", "250"]));
+        assert_eq!(params.fetch.jitter, Duration::from_millis(250));
+        assert!(Params::from_opts(Opts::parse_from(["async_streams"])).fetch.jitter.is_zero());
 
-#[cfg(test)]
-mod tests {
-    use std::time::{Duration, UNIX_EPOCH};
-    use yahoo::time::macros::datetime;
-    use yahoo::YahooError;
-    use std::str::FromStr;
-    use super::*;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let delays: Vec<Duration> = (0..1_000).map(|_| jitter_delay(&mut rng, params.fetch.jitter)).collect();
+        assert!(delays.iter().all(|delay| *delay <= Duration::from_millis(250)));
+        // spread over the range rather than stuck at one end
+        assert!(delays.iter().any(|delay| *delay < Duration::from_millis(50)));
+        assert!(delays.iter().any(|delay| *delay > Duration::from_millis(200)));
+        let mut again = rand::rngs::StdRng::seed_from_u64(7);
+        assert_eq!(jitter_delay(&mut again, params.fetch.jitter), delays[0]);
+        assert_eq!(jitter_delay(&mut rng, Duration::ZERO), Duration::ZERO);
+    }
 
-    #[ignore]
     #[test]
-    fn it_loads_params() {
-        let params = Params::default();
-        //println!("{:?}", &params);
-        assert_eq!(params.symbols.len(), 5usize);
+    fn it_writes_run_metadata_beside_file_outputs() -> Result<(),Error> {
+        let path = std::env::temp_dir().join(format!("async_streams_meta_{}.csv", std::process::id())).to_string_lossy().to_string();
+        let sink = format!("{}:csv", path);
+        let params = Params::from_opts(Opts::parse_from([
+            "async_streams", "--symbols", "MSFT,AAPL", "--from", "2024-06-03T00:00:00Z", "--to", "2024-06-28T00:00:00Z",
+            "--sma-window", "5", "--resample", "weekly", "--sink", &sink, "--sink=-:json",
+        ]));
+        let generated_at = Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap();
+        write_metadata(&params, generated_at, None)?;
+
+        let sidecar = RunMetadata::path_for(&path);
+        assert_eq!(sidecar.to_string_lossy(), format!("{}.meta.json", path));
+        let metadata: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&sidecar)?)?;
+        assert_eq!(metadata["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata["generated_at"], "2024-07-01T12:00:00+00:00");
+        assert_eq!(metadata["symbols"], serde_json::json!(["MSFT", "AAPL"]));
+        assert_eq!(metadata["start"], "2024-06-03T00:00:00+00:00");
+        assert_eq!(metadata["end"], "2024-06-28T00:00:00+00:00");
+        assert_eq!(metadata["interval"], "1d");
+        assert_eq!(metadata["resample"], "weekly");
+        assert_eq!(metadata["windows"], serde_json::json!({ "sma_window": 5, "rsi_period": 14 }));
+        assert_eq!(metadata["columns"].as_array().unwrap().len(), params.settings.columns.len());
+        assert_eq!(metadata["conversion"], serde_json::Value::Null);
+        // nothing is written for stdout
+        assert!(!RunMetadata::path_for("-").exists());
+        std::fs::remove_file(&sidecar)?;
+        Ok(())
+    }
+
+    ///
+    /// Rate limits the first `limited` requests, recording how many requests were in flight as each one was made.
+    ///
+    struct RateLimitingProvider {
+        limited: usize,
+        calls: Mutex<Vec<usize>>,
+        in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl QuoteProvider for RateLimitingProvider {
+        async fn get_quote_history(&self, _symbol: &str, _start: &DateTime<Utc>, _end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+            use std::sync::atomic::Ordering::SeqCst;
+            let in_flight = self.in_flight.fetch_add(1, SeqCst) + 1;
+            let call = {
+                let mut calls = self.calls.lock().unwrap();
+                calls.push(in_flight);
+                calls.len()
+            };
+            tokio_time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, SeqCst);
+            if call <= self.limited {
+                Err(FetchError::RateLimited)
+            } else {
+                Ok(vec![quote(1_717_372_800, 10.0), quote(1_717_459_200, 11.0)])
+            }
+        }
     }
 
     #[tokio::test]
-    async fn it_gets_closing_data() -> Result<(),Error>{
-        let symbol = "AAPL";
-        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
-        let end: DateTime<Utc> = DateTime::from_str("2020-1-31 23:59:59.99 UTC").unwrap();
-        let data = fetch_closing_data(symbol, &start, &end).await?;
-        println!("{:?}", &data);
+    async fn it_backs_off_when_rate_limited_and_recovers() {
+        let provider = RateLimitingProvider { limited: 4, calls: Mutex::new(Vec::new()), in_flight: Default::default() };
+        let throttle = Throttle::new(4, Duration::from_millis(50));
+        let (start, end) = (Utc.timestamp_opt(1_717_286_400, 0).unwrap(), Utc.timestamp_opt(1_717_545_600, 0).unwrap());
+        let options = FetchOptions::default();
+        let (source, start, end, options) = (&provider, &start, &end, &options);
+        let fetch = |symbol: &'static str| throttled(&throttle, move || fetch_closing_data(source, symbol, start, end, options));
+        let results = tokio::join!(fetch("A"), fetch("B"), fetch("C"), fetch("D"));
+        let results = [results.0.0, results.1.0, results.2.0, results.3.0];
+
+        assert!(results.iter().all(|closes| closes == &Ok(vec![10.0, 11.0])));
+        let calls = provider.calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 8);
+        // all four went at once and were rate limited, which took the limit down to one
+        assert_eq!(calls[..4], [1, 2, 3, 4]);
+        assert_eq!(calls[4], 1);
+        assert!(calls[5..].iter().all(|in_flight| *in_flight < 4));
+        // each success handed a permit back
+        assert_eq!(throttle.limit(), 4);
+
+        throttle.rate_limited();
+        throttle.rate_limited();
+        assert_eq!(throttle.limit(), 1);
+        throttle.rate_limited();
+        assert_eq!(throttle.limit(), 1);
+        throttle.succeeded();
+        assert_eq!(throttle.limit(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_splits_the_output_by_symbol() -> Result<(),Error> {
+        let dir = std::env::temp_dir().join(format!("async_streams_split_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let sink = format!("{}:csv", dir.join("data.csv").to_string_lossy());
+        let mut params = Params::from_opts(Opts::parse_from([
+            "async_streams", "--symbols", "MSFT,AAPL", "--sink", &sink, "--sink=-:json", "--split-by-symbol", "--quiet",
+        ]));
+        let paths: Vec<String> = params.sinks.iter().map(|sink| sink.path.clone()).collect();
+        let (msft, aapl) = (dir.join("data_MSFT.csv"), dir.join("data_AAPL.csv"));
+        assert_eq!(paths, vec![msft.to_string_lossy().to_string(), aapl.to_string_lossy().to_string(), "-".to_string()]);
+        params.sinks.retain(|sink| !sink.is_stdout());
+
+        let provider = Arc::new(MockProvider {
+            quotes: vec![quote(1_717_372_800, 10.0), quote(1_717_459_200, 11.0)],
+            slow: vec![],
+            delay: Duration::ZERO,
+        });
+        let mut sinks = params.sinks
+            .iter()
+            .map(|sink| sink.open(&params.settings.columns, params.precision, false, DEFAULT_BUFFER_SIZE))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        stream_signals(&params, provider, &mut sinks, &mut Vec::new()).await?;
+        drop(sinks);
+
+        for (path, symbol) in [(msft, "MSFT"), (aapl, "AAPL")] {
+            let csv = std::fs::read_to_string(&path)?;
+            let lines: Vec<&str> = csv.lines().collect();
+            assert_eq!(lines.len(), 2);
+            assert!(lines[0].starts_with("period start,symbol,"));
+            assert_eq!(lines[1].split(',').nth(1), Some(symbol));
+        }
+        assert_eq!(SinkSpec::from_str("report.json:csv").unwrap().for_symbol("SPY").path, "report_SPY.json");
+        assert_eq!(SinkSpec::from_str("out:csv").unwrap().for_symbol("SPY").path, "out_SPY");
+        std::fs::remove_dir_all(&dir)?;
         Ok(())
     }
 
+    ///
+    /// Fails every request with `error`, counting them.
+    ///
+    struct AlwaysFailingProvider {
+        error: FetchError,
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    #[async_trait]
+    impl QuoteProvider for AlwaysFailingProvider {
+        async fn get_quote_history(&self, _: &str, _: &DateTime<Utc>, _: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Err(self.error.clone())
+        }
+    }
+
     #[tokio::test]
-    async fn it_streams_signals() -> Result<(),Error>{
-        let symbols = vec!["AAPL".to_string()];
-        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
-        let end: DateTime<Utc> = DateTime::from_str("2020-1-31 23:59:59.99 UTC").unwrap();
-        let data = stream_signals(&symbols, &start, &end).await?;
-        println!("{:?}", &data);
+    async fn it_retries_the_same_failures_when_watching() {
+        let (start, end) = (Utc.timestamp_opt(1_717_286_400, 0).unwrap(), Utc.timestamp_opt(1_717_545_600, 0).unwrap());
+        for (error, attempts) in [
+            (FetchError::Network("reset".to_string()), FETCH_ATTEMPTS),
+            (FetchError::RateLimited, FETCH_ATTEMPTS),
+            (FetchError::TimedOut, 1),
+            (FetchError::NotFound, 1),
+        ] {
+            let provider = Arc::new(AlwaysFailingProvider { error, calls: Default::default() });
+            print_signal_row(provider.clone(), "AAPL", start, end, 0).await;
+            // the retries are spawned, so give them a moment to run
+            tokio_time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(provider.calls.load(std::sync::atomic::Ordering::SeqCst), attempts, "{}", provider.error);
+        }
+    }
+
+    #[tokio::test]
+    async fn it_records_fetch_telemetry() -> Result<(),Error> {
+        let provider = RateLimitingProvider { limited: 1, calls: Mutex::new(Vec::new()), in_flight: Default::default() };
+        let throttle = Throttle::new(1, Duration::from_millis(10));
+        let (start, end) = (Utc.timestamp_opt(1_717_286_400, 0).unwrap(), Utc.timestamp_opt(1_717_545_600, 0).unwrap());
+        let options = FetchOptions::default();
+        let (closes, telemetry) = throttled(&throttle, || fetch_closing_data(&provider, "MSFT", &start, &end, &options)).await;
+        assert_eq!(closes, Ok(vec![10.0, 11.0]));
+        assert_eq!(telemetry.retries, 1);
+        // both requests took the provider's 20ms
+        assert!(telemetry.latency >= Duration::from_millis(40));
+
+        // network errors are retried too, but not timeouts
+        let attempts = &std::sync::atomic::AtomicU32::new(0);
+        let failing = |e: FetchError| move || {
+            let attempt = attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let result = if attempt == 0 { Err(e.clone()) } else { Ok(attempt) };
+            async move { result }
+        };
+        let (attempt, telemetry) = throttled(&throttle, failing(FetchError::Network("reset".to_string()))).await;
+        assert_eq!((attempt, telemetry.retries), (Ok(1), 1));
+        attempts.store(0, std::sync::atomic::Ordering::SeqCst);
+        let (attempt, telemetry) = throttled(&throttle, failing(FetchError::TimedOut)).await;
+        assert_eq!((attempt, telemetry.retries), (Err(FetchError::TimedOut), 0));
+        attempts.store(0, std::sync::atomic::Ordering::SeqCst);
+        let (attempt, telemetry) = throttled(&throttle, || async { Err::<u32, _>(FetchError::Network("down".to_string())) }).await;
+        assert_eq!((attempt, telemetry.retries), (Err(FetchError::Network("down".to_string())), FETCH_ATTEMPTS - 1));
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT", "-q"]));
+        let provider = Arc::new(MockProvider { quotes: vec![quote(1, 10.0), quote(2, 11.0)], slow: vec![], delay: Duration::ZERO });
+        let summary = stream_signals(&params, provider, &mut [], &mut Vec::new()).await?;
+        assert_eq!(summary.telemetry.keys().collect::<Vec<_>>(), vec!["AAPL", "MSFT"]);
+        assert!(summary.telemetry.values().all(|telemetry| telemetry.retries == 0));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_validates_the_written_csv() -> Result<(),Error> {
+        let path = std::env::temp_dir().join(format!("async_streams_validate_{}.csv", std::process::id())).to_string_lossy().to_string();
+        let sink = format!("{}:csv", path);
+        let params = Params::from_opts(Opts::parse_from([
+            "async_streams", "-s", "AAPL,MSFT", "-q", "--sink", &sink, "--columns", "symbol,price,rsi,annualized_vol", "--validate-output",
+        ]));
+        assert!(params.validate_output);
+        let provider = Arc::new(MockProvider { quotes: vec![quote(1, 10.0), quote(2, 11.0)], slow: vec![], delay: Duration::ZERO });
+        let mut sinks = vec![params.sinks[0].open(&params.settings.columns, params.precision, false, DEFAULT_BUFFER_SIZE)?];
+        let summary = stream_signals(&params, provider, &mut sinks, &mut Vec::new()).await?;
+        drop(sinks);
+        assert_eq!(validate_outputs(&params, &summary, &HashMap::new()), Vec::<String>::new());
+        let written = std::fs::read_to_string(&path)?;
+        let parsed = StockRow::from_csv_columns(written.lines().nth(1).unwrap(), &params.settings.columns).unwrap();
+        assert_eq!((parsed.symbol.as_str(), parsed.price, parsed.rsi), ("AAPL", 11.0, None));
+
+        // a file a row short of what was written, or one longer
+        let short = StreamSummary { rows: 3, ..summary.clone() };
+        assert_eq!(validate_outputs(&params, &short, &HashMap::new()), vec![format!("{}: 2 rows, expected 3", path)]);
+        assert_eq!(validate_csv(&written, 2), Ok(()));
+        assert_eq!(validate_csv(&written, 1), Err("2 rows, expected 1".to_string()));
+
+        std::fs::write(&path, written.replacen("$11.00", "$1x.00", 1))?;
+        let problems = validate_outputs(&params, &summary, &HashMap::new());
+        assert_eq!(problems, vec![format!("{}: row 1: price '$1x.00' isn't a number", path)]);
+        std::fs::write(&path, written.replacen("$11.00", "$NaN", 1))?;
+        assert_eq!(validate_csv(&std::fs::read_to_string(&path)?, 2), Err("row 1: price isn't finite".to_string()));
+        assert_eq!(validate_csv("symbol,volume\nAAPL,1\n", 1), Err("unknown column 'volume' in the header".to_string()));
+        assert_eq!(INVALID_OUTPUT_EXIT_CODE, 5);
+        std::fs::remove_file(&path)?;
         Ok(())
     }
 
+    #[test]
+    fn it_derives_the_header_from_the_columns() {
+        let row = StockRow {
+            period_start: "2024-06-03T00:00:00+00:00".to_string(),
+            symbol: "AAPL".to_string(),
+            price: 20.0,
+            pct_change: Some(100.0),
+            period_min: Some(10.0),
+            period_max: Some(20.0),
+            last_sma: Some(14.33),
+            cum_return: Some(1.0),
+            annualized_vol: Some(0.25),
+            rsi: None,
+            rel_strength: Some(1.5),
+            last_quote: None,
+            currency: None,
+        };
+        let fields = match serde_json::to_value(&row).unwrap() {
+            serde_json::Value::Object(fields) => fields,
+            _ => unreachable!(),
+        };
+        let columns = [Column::Symbol, Column::Price, Column::Sma, Column::AnnualizedVol, Column::Rsi];
+        let header = StockRow::csv_header(&columns);
+        assert_eq!(header, "symbol,price,sma,annualized vol %,rsi");
+        // a heading for every cell, each naming the column it heads
+        let cells = row.to_csv_columns(&columns, 2);
+        assert_eq!(cells, "AAPL,$20.00,$14.33,25.00%,");
+        assert_eq!(header.split(',').count(), cells.split(',').count());
+        assert_eq!(header.split(',').map(Column::from_header).collect::<Vec<_>>(), columns.map(Some).to_vec());
+        assert_eq!(StockRow::csv_header(&Column::DEFAULT), "period start,symbol,price,change %,min,max,sma,cum return %");
+        // every column is a field of the row, the unset rsi aside
+        for column in Column::ALL.iter().filter(|column| **column != Column::Rsi) {
+            assert!(fields.contains_key(column.field()), "{}", column.field());
+        }
+    }
 
     #[tokio::test]
-    async fn it_gets_latest_quote() -> Result<(),YahooError>{
-        let provider = yahoo::YahooConnector::new().unwrap();
-        // get the latest quotes in 1 minute intervals
-        let response = provider.get_latest_quotes("AAPL", "1d").await?;
-        // extract just the latest valid quote summery
-        // including timestamp,open,close,high,low,volume
-        let quote = response.last_quote().unwrap();
-        let time: OffsetDateTime =
-            OffsetDateTime::from(UNIX_EPOCH + Duration::from_secs(quote.timestamp));
-        println!("At {} quote price of Apple was {}", time, quote.close);
+    async fn it_timestamps_the_output_files() -> Result<(),Error> {
+        let dir = std::env::temp_dir().join(format!("async_streams_timestamped_{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let sink = format!("{}:csv", dir.join("data.csv").to_string_lossy());
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--sink", &sink, "--sink=-:json", "--output-append-timestamp"]));
+        let name = std::path::Path::new(&params.sinks[0].path).file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.starts_with("data_") && name.ends_with("Z.csv") && name.len() == "data_20241014T093000.125Z.csv".len());
+        assert_eq!(params.sinks[1].path, "-");
+        assert!(Opts::try_parse_from(["async_streams", "--output-append-timestamp", "--incremental"]).is_err());
+
+        // two runs a moment apart, the second writing a different price
+        let spec = SinkSpec { path: dir.join("data.csv").to_string_lossy().to_string(), format: OutputFormat::Csv, symbol: None };
+        let first = Utc.with_ymd_and_hms(2024, 10, 14, 9, 30, 0).unwrap();
+        let mut paths = Vec::new();
+        for (at, close) in [(first, 10.0), (first + TimeDelta::milliseconds(125), 20.0)] {
+            let spec = spec.timestamped(&at);
+            let provider = Arc::new(MockProvider { quotes: vec![quote(1, close)], slow: vec![], delay: Duration::ZERO });
+            let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL", "-q"]));
+            let mut sinks = vec![spec.open(&params.settings.columns, params.precision, false, DEFAULT_BUFFER_SIZE)?];
+            stream_signals(&params, provider, &mut sinks, &mut Vec::new()).await?;
+            paths.push(spec.path);
+        }
+        assert_eq!(paths[0], dir.join("data_20241014T093000.000Z.csv").to_string_lossy());
+        assert_eq!(paths[1], dir.join("data_20241014T093000.125Z.csv").to_string_lossy());
+        assert!(std::fs::read_to_string(&paths[0])?.contains("$10.00"));
+        assert!(std::fs::read_to_string(&paths[1])?.contains("$20.00"));
+        assert!(!dir.join("data.csv").exists());
+        std::fs::remove_dir_all(&dir)?;
         Ok(())
     }
 
     #[tokio::test]
-    async fn it_gets_a_quote_range() -> Result<(),YahooError>{
-        let provider = yahoo::YahooConnector::new().unwrap();
-        let start = datetime!(2020-1-1 0:00:00.00 UTC);
-        let end = datetime!(2020-1-31 23:59:59.99 UTC);
-        // returns historic quotes with daily interval
-        let resp = provider.get_quote_history("AAPL", start, end).await?;
-        let quotes = resp.quotes().unwrap();
-        println!("Apple's quotes in January: {:?}", quotes);
+    async fn it_summarizes_without_writing_rows() -> Result<(),Error> {
+        let path = std::env::temp_dir().join(format!("async_streams_summary_only_{}.csv", std::process::id())).to_string_lossy().to_string();
+        let sink = format!("{}:csv", path);
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT,TSLA", "--sink", &sink, "--summary-only"]));
+        assert!(params.summary_only && params.quiet);
+        let provider = Arc::new(FailingProvider {
+            quotes: vec![quote(1, 10.0), quote(2, 11.0)],
+            failing: vec![("TSLA".to_string(), FetchError::NotFound)],
+        });
+        let mut console = Vec::new();
+        let summary = summarize(&params, provider, &mut console).await?;
 
+        assert!(!std::path::Path::new(&path).exists());
+        assert!(!RunMetadata::path_for(&path).exists());
+        assert_eq!((summary.rows, summary.failed.len()), (2, 1));
+        assert_eq!(summary.telemetry.len(), 3);
+        let console = String::from_utf8(console).unwrap();
+        let lines: Vec<&str> = console.lines().collect();
+        assert_eq!(lines[..5], ["symbols: 3", "rows: 2", "failed: 1, TSLA (data not found)", "alerts breached: 0", "retries: 0"]);
+        assert!(lines[5].starts_with("fetch time: "));
+        assert_eq!(lines.len(), 6);
         Ok(())
     }
 
     #[tokio::test]
-    async fn it_retrieves_daily_quotes_for_the_last_month() -> Result<(),YahooError>{
-        let provider = yahoo::YahooConnector::new().unwrap();
-        let response = provider.get_quote_range("AAPL", "1d", "1mo").await?;
-        let quotes = response.quotes().unwrap();
-        println!("Apple's quotes of the last month: {:?}", quotes);
+    async fn it_writes_an_equal_weight_portfolio() -> Result<(),Error> {
+        let day = |n: u64| n * 86_400;
+        let series: HashMap<String, Vec<Quote>> = [
+            ("AAPL", [10.0, 12.0, 9.0, 15.0].iter().enumerate().map(|(n, close)| quote(day(n as u64), *close)).collect::<Vec<_>>()),
+            // starts a day later and misses day 2, so only days 1 and 3 are in common
+            ("MSFT", vec![quote(day(1), 50.0), quote(day(3), 25.0), quote(day(4), 30.0)]),
+        ].into_iter().map(|(symbol, quotes)| (symbol.to_string(), quotes)).collect();
+        let provider: Arc<dyn QuoteProvider> = Arc::new(SeriesProvider { series });
+
+        let path = std::env::temp_dir().join(format!("async_streams_portfolio_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT", "--portfolio", &path, "-q"]));
+        assert_eq!(params.portfolio.as_deref(), Some(path.as_str()));
+        let portfolio = write_portfolio(&params, provider, &path).await?;
+        assert_eq!(portfolio.symbols, vec!["AAPL", "MSFT"]);
+        assert_eq!(portfolio.dates, vec!["1970-01-02", "1970-01-04"]);
+        // AAPL rebased from 12 to 15 is 1.25, MSFT from 50 to 25 is 0.5
+        assert_eq!(portfolio.equity, vec![1.0, 0.875]);
+        assert!((portfolio.cumulative_return + 0.125).abs() < 1e-12);
+        assert!((portfolio.max_drawdown - 0.125).abs() < 1e-12);
 
+        let csv = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(csv, "date,equity\n1970-01-02,1.0000\n1970-01-04,0.8750\n");
         Ok(())
     }
 
     #[tokio::test]
-    async fn it_finds_all_matching_tickers() -> Result<(),YahooError>{
-        let provider = yahoo::YahooConnector::new().unwrap();
-        let resp = provider.search_ticker("S&P500").await?;
+    async fn it_calculates_on_the_price_source() -> Result<(),Error> {
+        let bar = |timestamp: u64, open: f64, high: f64, low: f64, close: f64| Quote { timestamp, open, high, low, volume: 0, close, adjclose: close / 2.0 };
+        let quotes = vec![bar(1, 9.0, 12.0, 6.0, 9.0), bar(2, 10.0, 14.0, 8.0, 11.0), bar(3, 13.0, 16.0, 10.0, 13.0)];
+        let provider = MockProvider { quotes, slow: vec![], delay: tokio_time::Duration::ZERO };
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let end: DateTime<Utc> = DateTime::from_str("2020-1-31 0:00:00.00 UTC").unwrap();
+        let closes = |args: &[&str]| {
+            let params = Params::from_opts(Opts::parse_from(["async_streams"].iter().chain(args)));
+            let provider = &provider;
+            async move { (params.fetch.price_source, fetch_closing_data(provider, "AAPL", &start, &end, &params.fetch).await.unwrap()) }
+        };
 
-        println!("All tickers found while searching for 'S&P500':");
-        let items = resp.quotes.iter();
-        items.for_each(|item| println!("{}", item.symbol));
+        assert_eq!(closes(&[]).await, (PriceSource::Adjclose, vec![4.5, 5.5, 6.5]));
+        assert_eq!(closes(&["--price-source", "close"]).await, (PriceSource::Close, vec![9.0, 11.0, 13.0]));
+        assert_eq!(closes(&["--price-source", "typical"]).await, (PriceSource::Typical, vec![9.0, 11.0, 13.0]));
+        assert_eq!(closes(&["--price-source", "HLC3"]).await, (PriceSource::Hlc3, vec![9.0, 11.0, 13.0]));
+        assert_eq!(closes(&["--price-source", "ohlc4"]).await, (PriceSource::Ohlc4, vec![9.0, 10.75, 13.0]));
+        assert!(Opts::try_parse_from(["async_streams", "--price-source", "median"]).is_err());
+        assert!(Opts::try_parse_from(["async_streams", "--price-source", "close", "--use-adjusted", "false"]).is_err());
 
+        // the signals run on the typical prices as they would on closes
+        let (_, typical) = closes(&["--price-source", "typical"]).await;
+        let settings = SignalSettings { sma_window: 2, ..SignalSettings::default() };
+        let row = calculate_signals("AAPL", &start, &typical, &settings).await;
+        assert_eq!(row.price, 13.0);
+        assert_eq!((row.period_min, row.period_max), (Some(9.0), Some(13.0)));
+        assert_eq!(row.last_sma, Some(12.0));
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_price_difference_calculate() {
-        use signals::PriceDifference;
+    async fn it_guards_against_long_gaps() -> Result<(),Error> {
+        let day = |n: u64| 1_704_067_200 + n * 86_400;
+        // a couple of weeks of quotes, 60 days of nothing, then a few more
+        let quotes: Vec<Quote> = (0..10).chain(69..74).map(|n| quote(day(n), 10.0 + n as f64)).collect();
+        let provider = MockProvider { quotes: quotes.clone(), slow: vec![], delay: tokio_time::Duration::ZERO };
+        let (start, end) = (DateTime::from_timestamp(day(0) as i64, 0).unwrap(), DateTime::from_timestamp(day(80) as i64, 0).unwrap());
+        assert_eq!(longest_gap(&to_bars(&quotes, PriceSource::Adjclose)), Some(60));
+        assert_eq!(longest_gap(&to_bars(&quotes[..1], PriceSource::Adjclose)), None);
 
-        let signal = PriceDifference {};
-        assert_eq!(signal.calculate(&[]).await, None);
-        assert_eq!(signal.calculate(&[1.0]).await, Some((0.0, 0.0)));
-        assert_eq!(signal.calculate(&[1.0, 0.0]).await, Some((-1.0, -1.0)));
-        assert_eq!(
-            signal.calculate(&[2.0, 3.0, 5.0, 6.0, 1.0, 2.0, 10.0]).await,
-            Some((8.0, 4.0))
-        );
-        assert_eq!(
-            signal.calculate(&[0.0, 3.0, 5.0, 6.0, 1.0, 2.0, 1.0]).await,
-            Some((1.0, 1.0))
-        );
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--max-gap-days", "60"]));
+        assert_eq!(fetch_closing_data(&provider, "AAPL", &start, &end, &params.fetch).await?.len(), 15);
+        // over the limit only warns, unless strict
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--max-gap-days", "30"]));
+        assert_eq!(params.fetch.max_gap_days, Some(30));
+        assert_eq!(fetch_closing_data(&provider, "AAPL", &start, &end, &params.fetch).await?.len(), 15);
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--max-gap-days", "30", "--strict"]));
+        assert_eq!(fetch_closing_data(&provider, "AAPL", &start, &end, &params.fetch).await, Err(FetchError::Sparse(60)));
+
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT", "--max-gap-days", "30", "--strict", "-q"]));
+        let mut console = Vec::new();
+        let summary = stream_signals(&params, Arc::new(provider), &mut [], &mut console).await?;
+        assert_eq!(summary.rows, 0);
+        assert_eq!(summary.failed, vec![("AAPL".to_string(), FetchError::Sparse(60)), ("MSFT".to_string(), FetchError::Sparse(60))]);
+        assert_eq!(summary.exit_code(true), FAILED_EXIT_CODE);
+        Ok(())
     }
 
     #[tokio::test]
-    async fn test_min_price_calculate() {
-        use signals::MinPrice;
+    async fn it_adds_a_relative_strength_column() -> Result<(),Error> {
+        let day = |n: u64| n * 86_400;
+        let series: HashMap<String, Vec<Quote>> = [
+            ("SPY", vec![quote(day(1), 100.0), quote(day(2), 105.0), quote(day(3), 110.0), quote(day(4), 120.0)]),
+            // doubles while the benchmark rises a fifth
+            ("AAPL", vec![quote(day(1), 10.0), quote(day(2), 12.0), quote(day(4), 20.0)]),
+            // only trades the days the benchmark rose 100 to 110, a tenth, so its rise of a fifth is 1.2 / 1.1
+            ("MSFT", vec![quote(day(0), 1.0), quote(day(1), 50.0), quote(day(3), 60.0)]),
+        ].into_iter().map(|(symbol, quotes)| (symbol.to_string(), quotes)).collect();
+        let provider: Arc<dyn QuoteProvider> = Arc::new(SeriesProvider { series });
 
-        let signal = MinPrice {};
-        assert_eq!(signal.calculate(&[]).await, None);
-        assert_eq!(signal.calculate(&[1.0]).await, Some(1.0));
-        assert_eq!(signal.calculate(&[1.0, 0.0]).await, Some(0.0));
-        assert_eq!(
-            signal.calculate(&[2.0, 3.0, 5.0, 6.0, 1.0, 2.0, 10.0]).await,
-            Some(1.0)
-        );
-        assert_eq!(
-            signal.calculate(&[0.0, 3.0, 5.0, 6.0, 1.0, 2.0, 1.0]).await,
-            Some(0.0)
-        );
+        let mut params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT", "--rs-benchmark", "spy", "-q"]));
+        assert_eq!(params.rs_benchmark.as_deref(), Some("SPY"));
+        assert_eq!(params.settings.columns.last(), Some(&Column::RelStrength));
+        load_benchmark(&mut params, provider.as_ref()).await;
+        assert_eq!(params.benchmark.as_ref().map(Vec::len), Some(4));
+        let sink = MemorySink::default();
+        stream_signals(&params, provider.clone(), &mut [sink.boxed()], &mut std::io::sink()).await?;
+        let rows = sink.rows.lock().unwrap().clone();
+        assert!((rows[0].rel_strength.unwrap() - 2.0 / 1.2).abs() < 1e-12);
+        assert!((rows[1].rel_strength.unwrap() - 1.2 / 1.1).abs() < 1e-12);
+        assert_eq!(rows[0].cell(Column::RelStrength, 2), "1.67");
+
+        // a benchmark without data leaves the column out rather than failing the symbols
+        let mut params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL,MSFT", "--rs-benchmark", "QQQ", "-q"]));
+        load_benchmark(&mut params, provider.as_ref()).await;
+        assert_eq!(params.benchmark, None);
+        assert_eq!(params.settings.columns, Column::DEFAULT.to_vec());
+        let sink = MemorySink::default();
+        let summary = stream_signals(&params, provider, &mut [sink.boxed()], &mut std::io::sink()).await?;
+        assert_eq!(summary.rows, 2);
+        assert!(sink.rows.lock().unwrap().iter().all(|row| row.rel_strength.is_none()));
+        Ok(())
     }
 
     #[tokio::test]
-    async fn test_max_price_calculate() {
-        use signals::MaxPrice;
+    async fn it_applies_the_bad_data_policy() -> Result<(),Error> {
+        let quotes = vec![quote(1, 10.0), quote(2, 0.0), quote(3, f64::NAN), quote(4, 12.0), quote(5, -1.0)];
+        let provider = MockProvider { quotes, slow: vec![], delay: tokio_time::Duration::ZERO };
+        let start: DateTime<Utc> = DateTime::from_str("2020-1-1 0:00:00.00 UTC").unwrap();
+        let end: DateTime<Utc> = DateTime::from_str("2020-1-31 0:00:00.00 UTC").unwrap();
+        let closes = |policy: &str| {
+            let params = Params::from_opts(Opts::parse_from(["async_streams", "--on-bad-data", policy]));
+            let provider = &provider;
+            async move { fetch_closing_data(provider, "AAPL", &start, &end, &params.fetch).await }
+        };
 
-        let signal = MaxPrice {};
-        assert_eq!(signal.calculate(&[]).await, None);
-        assert_eq!(signal.calculate(&[1.0]).await, Some(1.0));
-        assert_eq!(signal.calculate(&[1.0, 0.0]).await, Some(1.0));
-        assert_eq!(
-            signal.calculate(&[2.0, 3.0, 5.0, 6.0, 1.0, 2.0, 10.0]).await,
-            Some(10.0)
-        );
-        assert_eq!(
-            signal.calculate(&[0.0, 3.0, 5.0, 6.0, 1.0, 2.0, 1.0]).await,
-            Some(6.0)
-        );
+        assert_eq!(Params::from_opts(Opts::parse_from(["async_streams"])).fetch.on_bad_data, BadDataPolicy::DropPoints);
+        assert_eq!(closes("drop-points").await, Ok(vec![10.0, 12.0]));
+        assert_eq!(closes("skip-symbol").await, Ok(vec![]));
+        assert_eq!(closes("error").await, Err(FetchError::BadData(3)));
+        assert!(Opts::try_parse_from(["async_streams", "--on-bad-data", "ignore"]).is_err());
+
+        // a skipped symbol is one without data, an erroring one a failure
+        let provider = Arc::new(provider);
+        for (policy, failed, code) in [("skip-symbol", FetchError::Empty, 0), ("error", FetchError::BadData(3), FAILED_EXIT_CODE)] {
+            let params = Params::from_opts(Opts::parse_from(["async_streams", "-s", "AAPL", "-q", "--on-bad-data", policy]));
+            let summary = stream_signals(&params, provider.clone(), &mut [], &mut std::io::sink()).await?;
+            assert_eq!((summary.rows, summary.failed.clone()), (0, vec![("AAPL".to_string(), failed)]));
+            assert_eq!(summary.exit_code(false), code);
+        }
+        Ok(())
     }
 
     #[tokio::test]
-    async fn test_windowed_sma_calculate() {
-        use signals::WindowedSMA;
+    async fn it_annualizes_at_the_interval() {
+        let factors: Vec<(&str, f64)> = Interval::ALL.iter().map(|interval| (interval.as_str(), interval.annualization_factor())).collect();
+        assert_eq!(factors, vec![
+            ("1m", 98_280.0), ("5m", 19_656.0), ("15m", 6_552.0), ("30m", 3_276.0), ("1h", 1_638.0),
+            ("1d", 252.0), ("1wk", 52.0), ("1mo", 12.0),
+        ]);
+        assert_eq!((Resample::Weekly.annualization_factor(), Resample::Monthly.annualization_factor()), (52.0, 12.0));
 
-        let series = vec![2.0, 4.5, 5.3, 6.5, 4.7];
+        let periods = |args: &[&str]| Params::from_opts(Opts::parse_from(["async_streams"].iter().chain(args))).settings.periods_per_year;
+        assert_eq!(periods(&[]), 252.0);
+        assert_eq!(periods(&["--interval", "1wk"]), 52.0);
+        assert_eq!(periods(&["--interval", "1h"]), 1_638.0);
+        // resampled bars are annualized at their period, not that of the bars they were made from
+        assert_eq!(periods(&["--resample", "monthly"]), 12.0);
 
-        let signal = WindowedSMA::new(3);
-        assert_eq!(
-            signal.calculate(&series).await,
-            Some(vec![3.9333333333333336, 5.433333333333334, 5.5])
-        );
+        // configured trading days apply to daily bars and the sessions of intraday ones
+        let path = std::env::temp_dir().join(format!("async_streams_annualization_{}.toml", std::process::id()));
+        std::fs::write(&path, "trading_days_per_year = 250\n").unwrap();
+        let config = path.to_str().unwrap();
+        assert_eq!(periods(&["--config", config]), 250.0);
+        assert_eq!(periods(&["--config", config, "--interval", "30m"]), 3_250.0);
+        assert_eq!(periods(&["--config", config, "--interval", "1mo"]), 12.0);
+        std::fs::remove_file(&path).unwrap();
+
+        // the volatility of the same closes is annualized by the square root of the factor
+        let closes = [10.0, 11.0, 10.5, 12.0];
+        let mut volatility = Vec::new();
+        for periods_per_year in [252.0, 52.0] {
+            let settings = SignalSettings { columns: vec![Column::AnnualizedVol], periods_per_year, ..SignalSettings::default() };
+            volatility.push(calculate_signals("AAPL", &Utc::now(), &closes, &settings).await.annualized_vol.unwrap());
+        }
+        let (daily, weekly) = (volatility[0], volatility[1]);
+        assert!((daily / weekly - (252.0f64 / 52.0).sqrt()).abs() < 1e-12);
+    }
 
-        let signal = WindowedSMA::new( 5 );
-        assert_eq!(signal.calculate(&series).await, Some(vec![4.6]));
+    #[test]
+    fn it_buffers_the_outputs_as_configured() -> Result<(),Error> {
+        assert_eq!(Params::from_opts(Opts::parse_from(["async_streams"])).buffer_size, 8 * 1024);
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--buffer-size", "1048576"]));
+        assert_eq!(params.buffer_size, 1 << 20);
+        assert!(Opts::try_parse_from(["async_streams", "--buffer-size", "1024"]).is_ok());
+        let err = Opts::try_parse_from(["async_streams", "--buffer-size", "512"]).unwrap_err().to_string();
+        assert!(err.contains("at least 1024"));
+        assert!(Opts::try_parse_from(["async_streams", "--buffer-size", "lots"]).is_err());
+
+        let path = std::env::temp_dir().join(format!("async_streams_buffered_{}.csv", std::process::id()));
+        let mut writer = buffered(std::fs::File::create(&path)?, params.buffer_size);
+        assert_eq!(writer.capacity(), 1 << 20);
+        // nothing reaches the file until the buffer fills or is flushed
+        writer.write_all(&[b'x'; 4096])?;
+        assert_eq!(std::fs::metadata(&path)?.len(), 0);
+        writer.flush()?;
+        assert_eq!(std::fs::metadata(&path)?.len(), 4096);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_leaves_signals_it_cannot_calculate_empty() -> Result<(),Error> {
+        let start = Utc::now();
+        // three closes aren't enough for a 5 period SMA
+        let settings = SignalSettings { sma_window: 5, ..SignalSettings::default() };
+        let row = calculate_signals("AAPL", &start, &[10.0, 11.0, 12.0], &settings).await;
+        assert_eq!(row.last_sma, None);
+        assert_eq!(row.pct_change, Some(20.0));
+
+        let csv = row.to_csv_columns(&[Column::Symbol, Column::Price, Column::Sma], 2);
+        assert_eq!(csv, "AAPL,$12.00,");
+        assert_eq!(StockRow::from_csv_columns(&csv, &[Column::Symbol, Column::Price, Column::Sma]).unwrap().last_sma, None);
+
+        let mut writer = Vec::new();
+        write_signals(&mut JsonSink::with_columns(&mut writer, vec![Column::Symbol, Column::Sma]), &[row])?;
+        assert_eq!(String::from_utf8(writer).unwrap(), "{\"symbol\":\"AAPL\",\"last_sma\":null}\n");
 
-        let signal = WindowedSMA::new( 10 );
-        assert_eq!(signal.calculate(&series).await, Some(vec![]));
+        // selected signals stay in the line as nulls, those not selected stay out of it
+        let params = Params::from_opts(Opts::parse_from(["async_streams", "--columns", "symbol,sma,rsi", "--sma-window", "5", "-q"]));
+        let row = calculate_signals("AAPL", &start, &[10.0, 11.0, 12.0], &params.settings).await;
+        assert_eq!((row.period_min, row.period_max, row.pct_change), (None, None, None));
+        let mut writer = Vec::new();
+        write_signals(&mut JsonSink::with_columns(&mut writer, params.settings.columns.clone()), &[row])?;
+        assert_eq!(String::from_utf8(writer).unwrap(), "{\"symbol\":\"AAPL\",\"last_sma\":null,\"rsi\":null}\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_converts_normalized_prices_at_the_raw_rate() -> Result<(),Error> {
+        let day = |n: u64| 1_704_067_200 + n * 86_400;
+        let series: HashMap<String, Vec<Quote>> = [
+            ("AAPL", vec![quote(day(0), 40.0), quote(day(1), 44.0), quote(day(2), 50.0)]),
+            // a gap the symbols aren't allowed, which the rate is prepared without
+            ("USDEUR=X", vec![quote(day(0), 0.9), quote(day(20), 0.8)]),
+        ].into_iter().map(|(symbol, quotes)| (symbol.to_string(), quotes)).collect();
+        let provider: Arc<dyn QuoteProvider> = Arc::new(SeriesProvider { series });
+        let params = Params::from_opts(Opts::parse_from([
+            "async_streams", "-s", "AAPL", "--normalize", "--currency", "EUR", "--max-gap-days", "5", "--strict", "--limit-rows", "2", "-q",
+        ]));
+        let sink = MemorySink::default();
+        stream_signals(&params, provider, &mut [sink.boxed()], &mut Vec::new()).await?;
+        let rows = sink.rows.lock().unwrap();
+        // 50 rebased on 44 is about 113.64, at 0.8 rather than at the rebased rate of 100
+        assert!((rows[0].price - 50.0 / 44.0 * 100.0 * 0.8).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_intraday_intervals_for_joins_on_the_day() {
+        let opts = |args: &[&str]| validated(Opts::parse_from(["async_streams"].iter().chain(args)));
+        let err = opts(&["--interval", "1h", "--corr-matrix", "corr.csv"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+        assert!(err.to_string().contains("--corr-matrix"));
+        assert!(opts(&["--interval", "5m", "--portfolio", "equity.csv"]).is_err());
+        assert!(opts(&["--interval", "1wk", "--portfolio", "equity.csv"]).is_ok());
+        assert!(opts(&["--interval", "1h", "--rs-benchmark", "SPY"]).unwrap_err().to_string().contains("--rs-benchmark"));
+        assert!(opts(&["--rs-benchmark", "SPY"]).is_ok());
+        assert!(opts(&["--corr-matrix", "corr.csv"]).is_ok());
+        assert!(opts(&["--interval", "1h"]).is_ok());
     }
 }
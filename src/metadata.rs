@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+use serde::Serialize;
+
+///
+/// The parameters a run was made with, written beside each output file so the data can be reproduced.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunMetadata {
+    pub version: String,
+    /// When the run was made, RFC 3339 in UTC.
+    pub generated_at: String,
+    pub symbols: Vec<String>,
+    pub start: String,
+    pub end: String,
+    pub interval: String,
+    pub resample: Option<String>,
+    pub windows: SignalWindows,
+    pub columns: Vec<String>,
+    /// How the prices were converted out of dollars, or `None` when they weren't.
+    pub conversion: Option<CurrencyConversion>,
+}
+
+///
+/// The windows the signals were calculated over.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SignalWindows {
+    pub sma_window: usize,
+    pub rsi_period: usize,
+}
+
+///
+/// The rate prices were converted at and the FX symbol it was quoted by.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CurrencyConversion {
+    pub currency: String,
+    pub fx_symbol: String,
+    /// Units of `currency` per dollar.
+    pub rate: f64,
+}
+
+impl RunMetadata {
+    ///
+    /// The sidecar path for an output: the output's own path with `.meta.json` appended.
+    ///
+    pub fn path_for(output: &str) -> PathBuf {
+        PathBuf::from(format!("{}.meta.json", output))
+    }
+
+    ///
+    /// Write the metadata as pretty printed JSON beside `output`, returning the sidecar's path.
+    ///
+    pub fn write_beside(&self, output: &str) -> std::io::Result<PathBuf> {
+        let path = Self::path_for(output);
+        std::fs::write(&path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(path)
+    }
+}
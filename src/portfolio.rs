@@ -0,0 +1,66 @@
+use std::collections::BTreeSet;
+use serde::Serialize;
+use chrono::DateTime;
+use async_streams::Ohlcv;
+use crate::report::max_drawdown;
+
+///
+/// The equity curve of holding an equal weight of each symbol: every symbol's closes rebased to 1.0 on the first day,
+/// averaged across the symbols day by day.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Portfolio {
+    pub symbols: Vec<String>,
+    /// The days every symbol has a close, as `%Y-%m-%d`.
+    pub dates: Vec<String>,
+    pub equity: Vec<f64>,
+    /// The last point of the curve over the first, less one; 0 for an empty curve.
+    pub cumulative_return: f64,
+    /// Largest fall of the curve from a running peak, as a fraction of the peak.
+    pub max_drawdown: f64,
+}
+
+impl Portfolio {
+    ///
+    /// The curve over the days all of the symbols have a close, i.e. an inner join on the day of the timestamps, so
+    /// that symbols with longer or patchier histories are cut to the days in common.
+    ///
+    pub fn calculate(series: &[(String, Vec<Ohlcv>)]) -> Self {
+        let day = |bar: &Ohlcv| bar.timestamp / 86_400;
+        let common = series
+            .iter()
+            .map(|(_, bars)| bars.iter().map(day).collect::<BTreeSet<u64>>())
+            .reduce(|common, days| common.intersection(&days).copied().collect())
+            .unwrap_or_default();
+
+        let mut equity = vec![0.0; common.len()];
+        for (_, bars) in series {
+            let closes: Vec<f64> = bars.iter().filter(|bar| common.contains(&day(bar))).map(|bar| bar.close).collect();
+            for (point, close) in equity.iter_mut().zip(&closes) {
+                *point += close / closes[0] / series.len() as f64;
+            }
+        }
+        let dates = common
+            .iter()
+            .map(|day| DateTime::from_timestamp((day * 86_400) as i64, 0).unwrap_or_default().format("%Y-%m-%d").to_string())
+            .collect();
+        Self {
+            symbols: series.iter().map(|(symbol, _)| symbol.clone()).collect(),
+            dates,
+            cumulative_return: equity.last().map(|last| last / equity[0] - 1.0).unwrap_or(0.0),
+            max_drawdown: max_drawdown(&equity),
+            equity,
+        }
+    }
+
+    ///
+    /// The curve as CSV, one day per line.
+    ///
+    pub fn to_csv(&self, precision: usize) -> String {
+        let mut csv = String::from("date,equity\n");
+        for (date, equity) in self.dates.iter().zip(&self.equity) {
+            csv.push_str(&format!("{},{:.*}\n", date, precision, equity));
+        }
+        csv
+    }
+}
@@ -0,0 +1,57 @@
+use std::str::FromStr;
+use crate::provider::Quote;
+
+///
+/// The price of each bar the signals are calculated on, in place of its close.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriceSource {
+    Close,
+    /// The close adjusted for splits and dividends.
+    #[default]
+    Adjclose,
+    /// `(high + low + close) / 3`.
+    Typical,
+    /// The same as `Typical`, under the name some charting packages use.
+    Hlc3,
+    /// `(open + high + low + close) / 4`.
+    Ohlc4,
+}
+
+impl PriceSource {
+    pub const ALL: [PriceSource; 5] = [Self::Close, Self::Adjclose, Self::Typical, Self::Hlc3, Self::Ohlc4];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Close => "close",
+            Self::Adjclose => "adjclose",
+            Self::Typical => "typical",
+            Self::Hlc3 => "hlc3",
+            Self::Ohlc4 => "ohlc4",
+        }
+    }
+
+    ///
+    /// The quote's price from this source. The averages are of the raw prices, as only the close has an adjusted one.
+    ///
+    pub fn price(&self, quote: &Quote) -> f64 {
+        match self {
+            Self::Close => quote.close,
+            Self::Adjclose => quote.adjclose,
+            Self::Typical | Self::Hlc3 => (quote.high + quote.low + quote.close) / 3.0,
+            Self::Ohlc4 => (quote.open + quote.high + quote.low + quote.close) / 4.0,
+        }
+    }
+}
+
+impl FromStr for PriceSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        Self::ALL.into_iter().find(|source| source.as_str() == s).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(PriceSource::as_str).collect();
+            format!("unknown price source '{}', expected one of: {}", s, names.join(", "))
+        })
+    }
+}
@@ -0,0 +1,293 @@
+use async_trait::async_trait;
+use chrono::prelude::*;
+use time::OffsetDateTime;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use yahoo_finance_api as yahoo;
+use yahoo::YahooError;
+use crate::interval::Interval;
+
+pub use yahoo::Quote;
+
+///
+/// How a symbol's fetch went, for callers to inspect rather than read in the logs.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FetchTelemetry {
+    /// Requests made after the first.
+    pub retries: u32,
+    /// Time spent in the attempts, including the jitter ahead of each request but not any wait to be allowed to make
+    /// them.
+    pub latency: std::time::Duration,
+}
+
+///
+/// Why quotes couldn't be fetched. `Network`, `RateLimited` and `TimedOut` are transient, the rest will fail the same
+/// way again.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    Network(String),
+    RateLimited,
+    TimedOut,
+    NotFound,
+    Decode(String),
+    Empty,
+    /// The quotes have a gap of this many calendar days, longer than the run allows.
+    Sparse(i64),
+    /// This many of the closes are zero, negative or not finite.
+    BadData(usize),
+    /// The symbol's fetch or calculation task failed, e.g. it panicked.
+    Internal(String),
+}
+
+impl FetchError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Network(_) | Self::RateLimited | Self::TimedOut)
+    }
+
+    ///
+    /// Whether the fetch is worth making again: a timeout is transient too, but another attempt would only hold the
+    /// symbol up for as long again.
+    ///
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Network(_) | Self::RateLimited)
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Network(reason) => write!(f, "network error: {}", reason),
+            Self::RateLimited => write!(f, "rate limited"),
+            Self::TimedOut => write!(f, "fetch timed out"),
+            Self::NotFound => write!(f, "data not found"),
+            Self::Decode(reason) => write!(f, "invalid response: {}", reason),
+            Self::Empty => write!(f, "no quotes in range"),
+            Self::Sparse(days) => write!(f, "a gap of {} days between quotes", days),
+            Self::BadData(count) => write!(f, "{} close(s) not a positive number", count),
+            Self::Internal(reason) => write!(f, "internal error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<YahooError> for FetchError {
+    fn from(e: YahooError) -> Self {
+        match e {
+            // the status line of any response other than 200 OK
+            YahooError::FetchFailed(status) => match status.split_whitespace().next().and_then(|code| code.parse::<u16>().ok()) {
+                Some(429) => Self::RateLimited,
+                Some(code) if (400..500).contains(&code) => Self::NotFound,
+                _ => Self::Network(status),
+            },
+            YahooError::ConnectionFailed(e) => Self::Network(e.to_string()),
+            YahooError::BuilderFailed => Self::Network(e.to_string()),
+            YahooError::DeserializeFailed(e) => Self::Decode(e.to_string()),
+            YahooError::InvalidJson | YahooError::DataInconsistency => Self::Decode(e.to_string()),
+            YahooError::EmptyDataSet => Self::Empty,
+        }
+    }
+}
+
+impl From<FetchError> for Error {
+    fn from(e: FetchError) -> Self {
+        let kind = match e {
+            FetchError::Network(_) => ErrorKind::ConnectionRefused,
+            FetchError::RateLimited => ErrorKind::ConnectionRefused,
+            FetchError::TimedOut => ErrorKind::TimedOut,
+            FetchError::NotFound | FetchError::Empty => ErrorKind::NotFound,
+            FetchError::Decode(_) | FetchError::Sparse(_) | FetchError::BadData(_) => ErrorKind::InvalidData,
+            FetchError::Internal(_) => ErrorKind::Other,
+        };
+        Error::new(kind, e)
+    }
+}
+
+///
+/// A source of historical quotes.
+///
+#[async_trait]
+pub trait QuoteProvider: Send + Sync {
+
+    ///
+    /// Retrieve the quotes for `symbol` between `start` and `end`, in no particular order.
+    ///
+    /// # Returns
+    ///
+    /// The quotes, or a FetchError describing why they couldn't be retrieved.
+    ///
+    async fn get_quote_history(&self, symbol: &str, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError>;
+}
+
+///
+/// Quotes from Yahoo! Finance, daily unless another interval is given. Intraday quotes are those of the regular
+/// sessions unless asked to include the pre and post market ones too. Errors during download are mapped onto
+/// FetchErrors.
+///
+pub struct YahooProvider {
+    connector: yahoo::YahooConnector,
+    interval: Interval,
+    include_prepost: bool,
+}
+
+impl YahooProvider {
+    pub fn new() -> std::io::Result<Self> {
+        let connector = yahoo::YahooConnector::new()
+            .map_err(|_| Error::from(ErrorKind::ConnectionRefused))?;
+        Ok(Self { connector, interval: Interval::default(), include_prepost: false })
+    }
+
+    pub fn with_interval(mut self, interval: Interval) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    ///
+    /// Include the extended hours quotes of intraday intervals; daily and longer bars are the same either way.
+    ///
+    pub fn with_prepost(mut self, include_prepost: bool) -> Self {
+        self.include_prepost = include_prepost;
+        self
+    }
+}
+
+///
+/// The shortest of Yahoo's ranges reaching back at least `since` from now.
+///
+fn covering_range(since: chrono::TimeDelta) -> &'static str {
+    const RANGES: [(i64, &str); 9] =
+        [(1, "1d"), (5, "5d"), (31, "1mo"), (92, "3mo"), (183, "6mo"), (366, "1y"), (731, "2y"), (1827, "5y"), (3653, "10y")];
+    let days = since.num_days() + 1;
+    RANGES.iter().find(|(limit, _)| days <= *limit).map_or("max", |(_, range)| range)
+}
+
+///
+/// The quotes of an intraday response, leaving out those outside the regular sessions it lists unless
+/// `include_prepost`. A response listing no sessions is taken whole.
+///
+pub(crate) fn session_quotes(resp: &yahoo::YResponse, include_prepost: bool) -> Result<Vec<Quote>, FetchError> {
+    let quotes = resp.quotes()?;
+    if include_prepost {
+        return Ok(quotes);
+    }
+    let sessions: Vec<(u64, u64)> = resp.metadata()?
+        .trading_periods
+        .regular
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .map(|period| (period.start as u64, period.end as u64))
+        .collect();
+    if sessions.is_empty() {
+        return Ok(quotes);
+    }
+    Ok(quotes.into_iter().filter(|quote| sessions.iter().any(|(start, end)| (*start..*end).contains(&quote.timestamp))).collect())
+}
+
+#[async_trait]
+impl QuoteProvider for YahooProvider {
+    async fn get_quote_history(&self, symbol: &str, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+        if self.include_prepost && self.interval.is_intraday() {
+            // only a range back from now can ask for the extended hours too, so take one reaching the start and trim it
+            let range = covering_range(Utc::now() - *start);
+            let resp = self.connector.get_quote_period_interval(symbol, range, self.interval.as_str(), true).await?;
+            let (start, end) = (start.timestamp() as u64, end.timestamp() as u64);
+            return Ok(session_quotes(&resp, true)?.into_iter().filter(|quote| (start..=end).contains(&quote.timestamp)).collect());
+        }
+        // incompatibility between chron and time crates
+        let start = OffsetDateTime::from_unix_timestamp(start.timestamp()).unwrap();
+        let end = OffsetDateTime::from_unix_timestamp(end.timestamp()).unwrap();
+        let resp = self.connector.get_quote_history_interval(symbol, start, end, self.interval.as_str()).await?;
+        if self.interval.is_intraday() {
+            session_quotes(&resp, false)
+        } else {
+            Ok(resp.quotes()?)
+        }
+    }
+}
+
+///
+/// Quotes read from local CSV files, one per symbol at `template` with `{symbol}` replaced, for offline runs and
+/// backtests. The header names the columns: a `date` (RFC 3339, `YYYY-MM-DD` or unix seconds) and a `close` are required,
+/// `open`, `high`, `low`, `adjclose` and `volume` optional. Missing prices are the close and missing volume is 0. A file
+/// that doesn't exist is NotFound, one that can't be parsed a Decode error.
+///
+pub struct CsvProvider {
+    template: String,
+}
+
+impl CsvProvider {
+    pub fn new(template: &str) -> Self {
+        Self { template: template.to_string() }
+    }
+
+    pub fn path(&self, symbol: &str) -> String {
+        self.template.replace("{symbol}", symbol)
+    }
+
+    fn parse(contents: &str) -> Result<Vec<Quote>, String> {
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let header: Vec<String> = lines
+            .next()
+            .ok_or("no header")?
+            .split(',')
+            .map(|name| name.trim().to_lowercase().replace([' ', '_'], ""))
+            .collect();
+        let position = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+        let date = position(&["date", "timestamp"]).ok_or("no date column")?;
+        let close = position(&["close"]).ok_or("no close column")?;
+        let (open, high, low) = (position(&["open"]), position(&["high"]), position(&["low"]));
+        let (adjclose, volume) = (position(&["adjclose"]), position(&["volume"]));
+
+        lines
+            .enumerate()
+            .map(|(n, line)| {
+                let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+                let cell = |i: usize| cells.get(i).copied().ok_or_else(|| format!("line {}: missing column {}", n + 2, header[i]));
+                let price = |i: usize| cell(i)?.parse::<f64>().map_err(|e| format!("line {}: {}: {}", n + 2, header[i], e));
+                let close = price(close)?;
+                let or_close = |i: Option<usize>| i.map(price).unwrap_or(Ok(close));
+                Ok(Quote {
+                    timestamp: Self::timestamp(cell(date)?).ok_or_else(|| format!("line {}: invalid date '{}'", n + 2, cells[date]))?,
+                    open: or_close(open)?,
+                    high: or_close(high)?,
+                    low: or_close(low)?,
+                    volume: match volume {
+                        Some(i) => cell(i)?.parse::<f64>().map_err(|e| format!("line {}: volume: {}", n + 2, e))? as u64,
+                        None => 0,
+                    },
+                    close,
+                    adjclose: or_close(adjclose)?,
+                })
+            })
+            .collect()
+    }
+
+    fn timestamp(date: &str) -> Option<u64> {
+        if let Ok(seconds) = date.parse::<u64>() {
+            return Some(seconds);
+        }
+        let time = match DateTime::parse_from_rfc3339(date) {
+            Ok(time) => time.to_utc(),
+            Err(_) => NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?.and_time(NaiveTime::MIN).and_utc(),
+        };
+        u64::try_from(time.timestamp()).ok()
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for CsvProvider {
+    async fn get_quote_history(&self, symbol: &str, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Result<Vec<Quote>, FetchError> {
+        let path = self.path(symbol);
+        let contents = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Err(FetchError::NotFound),
+            Err(e) => return Err(FetchError::Network(format!("{}: {}", path, e))),
+        };
+        let quotes = Self::parse(&contents).map_err(|e| FetchError::Decode(format!("{}: {}", path, e)))?;
+        let range = start.timestamp()..=end.timestamp();
+        Ok(quotes.into_iter().filter(|quote| range.contains(&(quote.timestamp as i64))).collect())
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+use async_streams::{AsyncStockSignal, Ema, MaxPrice, MinPrice, Rsi, WindowedSMA};
+
+///
+/// A single JSON document of every symbol's full signal series, for charting rather than the last values of a row.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub start: String,
+    pub end: String,
+    pub parameters: ReportParameters,
+    pub symbols: BTreeMap<String, SymbolReport>,
+}
+
+///
+/// The windows the series were calculated over. The SMA and EMA share a window so that their points line up.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ReportParameters {
+    pub window: usize,
+    pub rsi_period: usize,
+}
+
+///
+/// The closes of a symbol with the series calculated from them, each ending at the last close, and scalar metrics over
+/// the whole period.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolReport {
+    pub closes: Vec<f64>,
+    pub sma: Vec<f64>,
+    pub ema: Vec<f64>,
+    pub rsi: Vec<f64>,
+    pub min: f64,
+    pub max: f64,
+    /// Largest fall from a running peak, as a fraction of the peak.
+    pub max_drawdown: f64,
+}
+
+impl SymbolReport {
+    pub async fn calculate(closes: Vec<f64>, parameters: &ReportParameters) -> Self {
+        Self {
+            sma: WindowedSMA::new(parameters.window).calculate(&closes).await.unwrap_or_default(),
+            ema: Ema::new(parameters.window).calculate(&closes).await.unwrap_or_default(),
+            rsi: Rsi::new(parameters.rsi_period).calculate(&closes).await.unwrap_or_default(),
+            min: MinPrice {}.calculate(&closes).await.unwrap_or(0.0),
+            max: MaxPrice {}.calculate(&closes).await.unwrap_or(0.0),
+            max_drawdown: max_drawdown(&closes),
+            closes,
+        }
+    }
+}
+
+///
+/// The largest fall from a running peak of the series, as a fraction of the peak; 0 for a series that never falls.
+///
+pub fn max_drawdown(series: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+    for price in series {
+        peak = peak.max(*price);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - price) / peak);
+        }
+    }
+    drawdown
+}
@@ -0,0 +1,87 @@
+use std::str::FromStr;
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta};
+use crate::signals::Ohlcv;
+
+///
+/// The period daily bars are aggregated into before the signals are calculated.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resample {
+    Weekly,
+    Monthly,
+}
+
+impl Resample {
+    ///
+    /// Aggregate time ordered bars into one bar per period (ISO week or calendar month, in UTC): the first open, highest
+    /// high, lowest low, last close and total volume, stamped with the period's first bar.
+    ///
+    pub fn apply(&self, bars: &[Ohlcv]) -> Vec<Ohlcv> {
+        let mut periods: Vec<((i32, u32), Ohlcv)> = Vec::new();
+        for bar in bars {
+            let key = self.period(bar.timestamp);
+            match periods.last_mut() {
+                Some((last_key, period)) if *last_key == key => {
+                    period.high = period.high.max(bar.high);
+                    period.low = period.low.min(bar.low);
+                    period.close = bar.close;
+                    period.volume += bar.volume;
+                }
+                _ => periods.push((key, *bar)),
+            }
+        }
+        periods.into_iter().map(|(_, bar)| bar).collect()
+    }
+
+    fn period(&self, timestamp: u64) -> (i32, u32) {
+        let date: NaiveDate = DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default().date_naive();
+        match self {
+            Self::Weekly => (date.iso_week().year(), date.iso_week().week()),
+            Self::Monthly => (date.year(), date.month()),
+        }
+    }
+}
+
+impl Resample {
+    ///
+    /// How far back a run without a start date looks, for a useful number of periods.
+    ///
+    pub fn default_lookback(&self) -> TimeDelta {
+        match self {
+            Self::Weekly => TimeDelta::weeks(52),
+            Self::Monthly => TimeDelta::days(5 * 365),
+        }
+    }
+
+    ///
+    /// The number of periods in a year, that figures per period are annualized with.
+    ///
+    pub fn annualization_factor(&self) -> f64 {
+        match self {
+            Self::Weekly => 52.0,
+            Self::Monthly => 12.0,
+        }
+    }
+
+    ///
+    /// The name the period is given on the command line.
+    ///
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+}
+
+impl FromStr for Resample {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "weekly" => Ok(Self::Weekly),
+            "monthly" => Ok(Self::Monthly),
+            other => Err(format!("unknown resample period '{}', expected one of: weekly, monthly", other)),
+        }
+    }
+}
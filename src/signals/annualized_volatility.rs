@@ -0,0 +1,28 @@
+use super::{std_dev, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnualizedVolatility {
+    periods_per_year: f64,
+}
+
+impl AnnualizedVolatility {
+    pub fn new(periods_per_year: f64) -> Self {
+        Self { periods_per_year }
+    }
+}
+
+///
+/// Standard deviation of the log returns scaled by `sqrt(periods_per_year)`. At least two returns (three prices) are
+/// needed, and every price must be positive for the log returns to exist.
+///
+impl AsyncStockSignal for AnnualizedVolatility {
+    type SignalType = f64;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if series.len() < 3 || series.iter().any(|p| *p <= 0.0) {
+            return None;
+        }
+        let returns: Vec<f64> = series.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+        Some(std_dev(&returns) * self.periods_per_year.sqrt())
+    }
+}
@@ -0,0 +1,57 @@
+use super::{AsyncOhlcSignal, AsyncStockSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Aroon(usize);
+
+impl Aroon {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+
+    fn lines(&self, highs: &[f64], lows: &[f64]) -> Option<Vec<(f64, f64)>> {
+        if self.0 == 0 || highs.len() <= self.0 {
+            return None;
+        }
+        let period = self.0 as f64;
+        // the latest of equal extremes counts, so a high retested today is as fresh as a new one
+        let since = |window: &[f64], better: fn(f64, f64) -> bool| {
+            let (latest, _) = window.iter().enumerate().fold((0, window[0]), |(at, best), (i, value)| {
+                if better(*value, best) || *value == best { (i, *value) } else { (at, best) }
+            });
+            (window.len() - 1 - latest) as f64
+        };
+        Some(
+            highs
+                .windows(self.0 + 1)
+                .zip(lows.windows(self.0 + 1))
+                .map(|(highs, lows)| {
+                    let up = 100.0 * (period - since(highs, |a, b| a > b)) / period;
+                    let down = 100.0 * (period - since(lows, |a, b| a < b)) / period;
+                    (up, down)
+                })
+                .collect(),
+        )
+    }
+}
+
+///
+/// Aroon as `(up, down)`: how recently the highest high and the lowest low of the trailing `window + 1` bars were made,
+/// as `100 * (window - bars since) / window`. A new extreme scores 100, one at the start of the lookback 0. Without
+/// bars the closes stand in for the highs and lows.
+///
+impl AsyncStockSignal for Aroon {
+    type SignalType = Vec<(f64, f64)>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        self.lines(series, series)
+    }
+}
+
+impl AsyncOhlcSignal for Aroon {
+    type SignalType = Vec<(f64, f64)>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<(f64, f64)>> {
+        let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+        let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+        self.lines(&highs, &lows)
+    }
+}
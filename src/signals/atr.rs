@@ -0,0 +1,48 @@
+use super::{AsyncOhlcSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Atr(usize);
+
+impl Atr {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// The true range of each bar after the first: the greatest of its own range and the gaps from the previous close.
+///
+pub(crate) fn true_range(bars: &[Ohlcv]) -> Vec<f64> {
+    bars.windows(2)
+        .map(|w| {
+            let (prev_close, bar) = (w[0].close, w[1]);
+            (bar.high - bar.low)
+                .max((bar.high - prev_close).abs())
+                .max((bar.low - prev_close).abs())
+        })
+        .collect()
+}
+
+///
+/// Average true range using Wilder's smoothing, seeded with the simple average of the first window of true ranges.
+///
+impl AsyncOhlcSignal for Atr {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        if bars.is_empty() || self.0 == 0 {
+            return None;
+        }
+        let ranges = true_range(bars);
+        if ranges.len() < self.0 {
+            return Some(vec![]);
+        }
+        let period = self.0 as f64;
+        let seed = ranges[..self.0].iter().sum::<f64>() / period;
+        let smoothed = ranges[self.0..].iter().scan(seed, |atr, tr| {
+            *atr = (*atr * (period - 1.0) + tr) / period;
+            Some(*atr)
+        });
+        Some(std::iter::once(seed).chain(smoothed).collect())
+    }
+}
@@ -0,0 +1,32 @@
+use super::{rolled_mean_std, rolling, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BollingerBands {
+    window: usize,
+    multiplier: f64,
+}
+
+impl BollingerBands {
+    pub fn new(window_size: usize, multiplier: f64) -> Self {
+        Self { window: window_size, multiplier }
+    }
+}
+
+///
+/// Bollinger Bands as `(lower, middle, upper)`: the simple moving average of each trailing window, offset either side
+/// by `multiplier` population standard deviations.
+///
+impl AsyncStockSignal for BollingerBands {
+    type SignalType = Vec<(f64, f64, f64)>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.window < 2 {
+            return None;
+        }
+        let mut stats = rolled_mean_std();
+        rolling(series, self.window, |window, dropped| {
+            let (mean, std) = stats(window, dropped);
+            (mean - self.multiplier * std, mean, mean + self.multiplier * std)
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use super::{mean, AsyncOhlcSignal, AsyncStockSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cci(usize);
+
+impl Cci {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+
+    fn cci(&self, typical: &[f64]) -> Option<Vec<f64>> {
+        if self.0 < 2 || typical.len() < self.0 {
+            return None;
+        }
+        Some(
+            typical
+                .windows(self.0)
+                .map(|w| {
+                    let sma = mean(w);
+                    let mad = w.iter().map(|tp| (tp - sma).abs()).sum::<f64>() / w.len() as f64;
+                    if mad == 0.0 {
+                        0.0
+                    } else {
+                        // unwrap is safe here as windows are never empty
+                        (w.last().unwrap() - sma) / (0.015 * mad)
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+///
+/// Commodity Channel Index of the typical price `(high + low + close) / 3`. Without bars the closes are used as the
+/// typical price. A window with no mean absolute deviation yields `0.0`; a series shorter than the window yields `None`.
+///
+impl AsyncStockSignal for Cci {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        self.cci(series)
+    }
+}
+
+impl AsyncOhlcSignal for Cci {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        let typical: Vec<f64> = bars.iter().map(|b| (b.high + b.low + b.close) / 3.0).collect();
+        self.cci(&typical)
+    }
+}
@@ -0,0 +1,42 @@
+use super::{AsyncOhlcSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChaikinMoneyFlow(usize);
+
+impl ChaikinMoneyFlow {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Chaikin Money Flow: the volume weighted money flow multiplier `((close - low) - (high - close)) / (high - low)` over
+/// each trailing window, between -1 (distribution) and 1 (accumulation). A bar without range has a multiplier of 0 and
+/// a window without volume a CMF of 0.
+///
+impl AsyncOhlcSignal for ChaikinMoneyFlow {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        if self.0 == 0 || bars.len() < self.0 {
+            return None;
+        }
+        let flows: Vec<(f64, f64)> = bars
+            .iter()
+            .map(|bar| {
+                let range = bar.high - bar.low;
+                let multiplier = if range == 0.0 { 0.0 } else { ((bar.close - bar.low) - (bar.high - bar.close)) / range };
+                (multiplier * bar.volume as f64, bar.volume as f64)
+            })
+            .collect();
+        Some(
+            flows
+                .windows(self.0)
+                .map(|window| {
+                    let volume: f64 = window.iter().map(|(_, volume)| volume).sum();
+                    if volume == 0.0 { 0.0 } else { window.iter().map(|(flow, _)| flow).sum::<f64>() / volume }
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,33 @@
+use super::{rolled_sum, rolling, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cmo(usize);
+
+impl Cmo {
+    pub fn new(period: usize) -> Self {
+        Self(period)
+    }
+}
+
+///
+/// Chande Momentum Oscillator: `100 * (gains - losses) / (gains + losses)` over the last `period` changes, where the
+/// gains and losses are the sizes of the rises and falls. Bounded by -100 for a window of only falls and 100 for one
+/// of only rises; a window without any movement is 0. `None` for a zero period or no more points than it.
+///
+impl AsyncStockSignal for Cmo {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Vec<f64>> {
+        if self.0 == 0 || series.len() <= self.0 {
+            return None;
+        }
+        let (gains, losses): (Vec<f64>, Vec<f64>) = series.windows(2).map(|w| ((w[1] - w[0]).max(0.0), (w[0] - w[1]).max(0.0))).unzip();
+        Some(
+            rolling(&gains, self.0, rolled_sum())?
+                .into_iter()
+                .zip(rolling(&losses, self.0, rolled_sum())?)
+                .map(|(gains, losses)| if gains + losses == 0.0 { 0.0 } else { 100.0 * (gains - losses) / (gains + losses) })
+                .collect(),
+        )
+    }
+}
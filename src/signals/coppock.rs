@@ -0,0 +1,36 @@
+use super::{weighted_means, AsyncStockSignal, RateOfChange};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoppockCurve {
+    roc1: usize,
+    roc2: usize,
+    wma: usize,
+}
+
+impl CoppockCurve {
+    pub fn new(roc1: usize, roc2: usize, wma: usize) -> Self {
+        Self { roc1, roc2, wma }
+    }
+}
+
+///
+/// Coppock Curve: the weighted moving average over `wma` points of the sum of the rates of change over `roc1` and
+/// `roc2` points. `None` unless the series is long enough for at least one value, i.e. longer than the longer rate of
+/// change by `wma - 1`.
+///
+impl AsyncStockSignal for CoppockCurve {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.wma == 0 {
+            return None;
+        }
+        let first = RateOfChange::new(self.roc1).calculate(series).await?;
+        let second = RateOfChange::new(self.roc2).calculate(series).await?;
+        // both end at the latest price, the shorter rate of change just starts earlier
+        let len = first.len().min(second.len());
+        let sums: Vec<f64> = first[first.len() - len..].iter().zip(&second[second.len() - len..]).map(|(a, b)| a + b).collect();
+        let curve = weighted_means(&sums, self.wma);
+        if curve.is_empty() { None } else { Some(curve) }
+    }
+}
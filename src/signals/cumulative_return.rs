@@ -0,0 +1,18 @@
+use super::AsyncStockSignal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CumulativeReturn;
+
+///
+/// Total return over the whole series, `(last / first) - 1.0`. A zero starting price has no meaningful return and yields `None`.
+///
+impl AsyncStockSignal for CumulativeReturn {
+    type SignalType = f64;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        match (series.first(), series.last()) {
+            (Some(first), Some(last)) if *first != 0.0 => Some(last / first - 1.0),
+            _ => None,
+        }
+    }
+}
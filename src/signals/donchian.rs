@@ -0,0 +1,37 @@
+use super::{rolling, AsyncOhlcSignal, AsyncStockSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DonchianChannel(usize);
+
+impl DonchianChannel {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+
+    fn channel(&self, highs: &[f64], lows: &[f64]) -> Option<Vec<(f64, f64)>> {
+        let upper = rolling(highs, self.0, |highs, _| highs.iter().fold(f64::MIN, |acc, h| acc.max(*h)))?;
+        let lower = rolling(lows, self.0, |lows, _| lows.iter().fold(f64::MAX, |acc, l| acc.min(*l)))?;
+        Some(lower.into_iter().zip(upper).collect())
+    }
+}
+
+///
+/// Donchian Channel as `(lower, upper)`: the lowest low and highest high over each trailing window. Without bars the
+/// closes stand in for the highs and lows.
+///
+impl AsyncStockSignal for DonchianChannel {
+    type SignalType = Vec<(f64, f64)>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        self.channel(series, series)
+    }
+}
+
+impl AsyncOhlcSignal for DonchianChannel {
+    type SignalType = Vec<(f64, f64)>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<(f64, f64)>> {
+        let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+        let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+        self.channel(&highs, &lows)
+    }
+}
@@ -0,0 +1,29 @@
+use super::{AsyncStockSignal, WindowedSMA};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dpo(usize);
+
+impl Dpo {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Detrended Price Oscillator: each price less the SMA ending `window / 2 + 1` prices earlier, which removes the trend
+/// to leave the shorter cycles. The first value is at the first price with such a displaced SMA behind it, so `None`
+/// is returned for windows below 2 or series shorter than `window + window / 2 + 1`.
+///
+impl AsyncStockSignal for Dpo {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        let shift = self.0 / 2 + 1;
+        if self.0 < 2 || series.len() < self.0 + shift {
+            return None;
+        }
+        let sma = WindowedSMA::new(self.0).calculate(series).await?;
+        // sma[k] ends at price k + window - 1, displaced from the price `shift` further on
+        Some(series[self.0 - 1 + shift..].iter().zip(&sma).map(|(price, sma)| price - sma).collect())
+    }
+}
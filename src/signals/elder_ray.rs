@@ -0,0 +1,28 @@
+use super::{AsyncOhlcSignal, AsyncStockSignal, Ema, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElderRay(usize);
+
+impl ElderRay {
+    pub fn new(period: usize) -> Self {
+        Self(period)
+    }
+}
+
+///
+/// Elder Ray as `(bull power, bear power)`: how far each bar's high and low are above the EMA of the closes over
+/// `period` bars. Bulls pushing the highs over the average make the first positive, bears pushing the lows under it the
+/// second negative. `None` for a zero period or fewer bars than it.
+///
+impl AsyncOhlcSignal for ElderRay {
+    type SignalType = Vec<(f64, f64)>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<(f64, f64)>> {
+        if self.0 == 0 || bars.len() < self.0 {
+            return None;
+        }
+        let closes: Vec<f64> = bars.iter().map(|bar| bar.close).collect();
+        let ema = Ema::new(self.0).calculate(&closes).await?;
+        Some(bars[self.0 - 1..].iter().zip(ema).map(|(bar, ema)| (bar.high - ema, bar.low - ema)).collect())
+    }
+}
@@ -0,0 +1,33 @@
+use super::AsyncStockSignal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ema(usize);
+
+impl Ema {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Exponential moving average with smoothing factor `2 / (window + 1)`, seeded with the simple average of the first window.
+///
+impl AsyncStockSignal for Ema {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if series.is_empty() || self.0 == 0 {
+            return None;
+        }
+        if series.len() < self.0 {
+            return Some(vec![]);
+        }
+        let alpha = 2.0 / (self.0 as f64 + 1.0);
+        let seed = series[..self.0].iter().sum::<f64>() / self.0 as f64;
+        let smoothed = series[self.0..].iter().scan(seed, |ema, price| {
+            *ema += alpha * (price - *ema);
+            Some(*ema)
+        });
+        Some(std::iter::once(seed).chain(smoothed).collect())
+    }
+}
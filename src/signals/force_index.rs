@@ -0,0 +1,27 @@
+use super::{AsyncOhlcSignal, AsyncStockSignal, Ema, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForceIndex(usize);
+
+impl ForceIndex {
+    pub fn new(period: usize) -> Self {
+        Self(period)
+    }
+}
+
+///
+/// Force Index: the EMA over `period` bars of each bar's move from the previous close times its volume, so heavy
+/// volume behind a rise pushes it up and behind a fall down. `None` for fewer than two bars or a zero period, empty
+/// until there are `period` moves.
+///
+impl AsyncOhlcSignal for ForceIndex {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        if self.0 == 0 || bars.len() < 2 {
+            return None;
+        }
+        let forces: Vec<f64> = bars.windows(2).map(|pair| (pair[1].close - pair[0].close) * pair[1].volume as f64).collect();
+        Ema::new(self.0).calculate(&forces).await
+    }
+}
@@ -0,0 +1,29 @@
+use super::{weighted_means, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HullMA(usize);
+
+impl HullMA {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Hull moving average `WMA(2 * WMA(window / 2) - WMA(window), sqrt(window))`, which cancels most of the lag of an
+/// average over the full window. The first value needs `window + sqrt(window) - 1` prices, shorter series have none.
+///
+impl AsyncStockSignal for HullMA {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if series.is_empty() || self.0 < 2 {
+            return None;
+        }
+        let full = weighted_means(series, self.0);
+        let half = weighted_means(series, self.0 / 2);
+        // both end at the latest price, the half window average just starts earlier
+        let raw: Vec<f64> = half[half.len() - full.len()..].iter().zip(&full).map(|(half, full)| 2.0 * half - full).collect();
+        Some(weighted_means(&raw, self.0.isqrt()))
+    }
+}
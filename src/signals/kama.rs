@@ -0,0 +1,41 @@
+use super::AsyncStockSignal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Kama {
+    period: usize,
+    fast: usize,
+    slow: usize,
+}
+
+impl Kama {
+    pub fn new(period: usize, fast: usize, slow: usize) -> Self {
+        Self { period, fast, slow }
+    }
+}
+
+///
+/// Kaufman Adaptive Moving Average: an EMA whose smoothing constant slides, squared, between that of a `slow` EMA and
+/// a `fast` one with the efficiency ratio of the last `period` prices, the net change over the sum of absolute changes.
+/// A trend moves it nearly as quickly as the fast EMA and noise barely moves it. Seeded with the price at `period - 1`,
+/// so `None` is returned unless the series is longer than `period`.
+///
+impl AsyncStockSignal for Kama {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.period == 0 || self.fast == 0 || self.slow == 0 || series.len() <= self.period {
+            return None;
+        }
+        let (fastest, slowest) = (2.0 / (self.fast as f64 + 1.0), 2.0 / (self.slow as f64 + 1.0));
+        let seed = series[self.period - 1];
+        let adapted = series.windows(self.period + 1).scan(seed, |kama, window| {
+            let change = (window[self.period] - window[0]).abs();
+            let volatility: f64 = window.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum();
+            let efficiency = if volatility == 0.0 { 0.0 } else { change / volatility };
+            let smoothing = (efficiency * (fastest - slowest) + slowest).powi(2);
+            *kama += smoothing * (window[self.period] - *kama);
+            Some(*kama)
+        });
+        Some(std::iter::once(seed).chain(adapted).collect())
+    }
+}
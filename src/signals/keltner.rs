@@ -0,0 +1,36 @@
+use super::{AsyncOhlcSignal, AsyncStockSignal, Atr, Ema, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeltnerChannels {
+    ema_period: usize,
+    atr_period: usize,
+    multiplier: f64,
+}
+
+impl KeltnerChannels {
+    pub fn new(ema_period: usize, atr_period: usize, multiplier: f64) -> Self {
+        Self { ema_period, atr_period, multiplier }
+    }
+}
+
+///
+/// Keltner Channels as `(lower, middle, upper)`, where the middle line is the EMA of the typical price and the bands are
+/// `multiplier` average true ranges either side of it. The series are aligned on their most recent values.
+///
+impl AsyncOhlcSignal for KeltnerChannels {
+    type SignalType = Vec<(f64, f64, f64)>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<(f64, f64, f64)>> {
+        let typical: Vec<f64> = bars.iter().map(|b| (b.high + b.low + b.close) / 3.0).collect();
+        let middle = Ema::new(self.ema_period).calculate(&typical).await?;
+        let atr = Atr::new(self.atr_period).calculate_ohlc(bars).await?;
+        let len = middle.len().min(atr.len());
+        Some(
+            middle[middle.len() - len..]
+                .iter()
+                .zip(&atr[atr.len() - len..])
+                .map(|(mid, atr)| (mid - self.multiplier * atr, *mid, mid + self.multiplier * atr))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,31 @@
+use super::{rolling, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinRegSlope(usize);
+
+impl LinRegSlope {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Slope of the least-squares line through each trailing window of closes, in price per period.
+///
+impl AsyncStockSignal for LinRegSlope {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.0 < 2 {
+            return None;
+        }
+        let n = self.0 as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let var_x: f64 = (0..self.0).map(|x| (x as f64 - mean_x).powi(2)).sum();
+        rolling(series, self.0, |window, _| {
+            let mean_y = window.iter().sum::<f64>() / n;
+            let cov: f64 = window.iter().enumerate().map(|(x, y)| (x as f64 - mean_x) * (y - mean_y)).sum();
+            cov / var_x
+        })
+    }
+}
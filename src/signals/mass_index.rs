@@ -0,0 +1,40 @@
+use super::{rolled_sum, rolling, AsyncOhlcSignal, AsyncStockSignal, Ema, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MassIndex {
+    ema_period: usize,
+    sum_period: usize,
+}
+
+impl MassIndex {
+    pub fn new(ema_period: usize, sum_period: usize) -> Self {
+        Self { ema_period, sum_period }
+    }
+}
+
+///
+/// Mass Index: the sum over `sum_period` bars of the ratio of the `ema_period` EMA of the high-low range to the EMA of
+/// that EMA. Each ratio is near 1 while ranges hold steady, so the index hovers around `sum_period` and bulges as they
+/// widen; classically a rise above 27 and fall back below 26.5 (with 9 and 25) warns of a reversal. A bar whose double
+/// EMA is 0 counts 1. `None` for zero periods or fewer than `2 * ema_period + sum_period - 2` bars.
+///
+impl AsyncOhlcSignal for MassIndex {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        if self.ema_period == 0 || self.sum_period == 0 || bars.len() + 2 < 2 * self.ema_period + self.sum_period {
+            return None;
+        }
+        let ranges: Vec<f64> = bars.iter().map(|bar| bar.high - bar.low).collect();
+        let ema = Ema::new(self.ema_period);
+        let single = ema.calculate(&ranges).await?;
+        let double = ema.calculate(&single).await?;
+        // the double EMA starts later, both end at the latest bar
+        let ratios: Vec<f64> = single[single.len() - double.len()..]
+            .iter()
+            .zip(&double)
+            .map(|(single, double)| if *double == 0.0 { 1.0 } else { single / double })
+            .collect();
+        rolling(&ratios, self.sum_period, rolled_sum())
+    }
+}
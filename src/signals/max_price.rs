@@ -1,5 +1,7 @@
 use super::AsyncStockSignal;
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MaxPrice;
 
 ///
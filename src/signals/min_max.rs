@@ -0,0 +1,22 @@
+use super::AsyncStockSignal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MinMax;
+
+///
+/// Find both the minimum and the maximum of a series of f64 in a single pass, skipping NaN.
+///
+/// # Returns
+///
+/// A tuple `(min, max)`, or `None` when there isn't a number in the series.
+///
+impl AsyncStockSignal for MinMax {
+    type SignalType = (f64, f64);
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        series.iter().filter(|price| !price.is_nan()).fold(None, |extremes, price| match extremes {
+            None => Some((*price, *price)),
+            Some((min, max)) => Some((price.min(min), price.max(max))),
+        })
+    }
+}
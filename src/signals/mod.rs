@@ -1,13 +1,89 @@
+use std::future::Future;
+
 mod price_diff;
 mod windowed_sma;
 mod max_price;
 mod min_price;
+mod cumulative_return;
+mod zscore;
+mod williams_r;
+mod ema;
+mod trix;
+mod cci;
+mod atr;
+mod keltner;
+mod donchian;
+mod annualized_volatility;
+mod parabolic_sar;
+mod linreg_slope;
+mod volatility;
+mod bollinger;
+mod chaikin_money_flow;
+mod rsi;
+mod spec;
+mod money_flow_index;
+mod dpo;
+mod aroon;
+mod hull_ma;
+mod rate_of_change;
+mod coppock;
+mod ulcer_index;
+mod twap;
+mod rolling_beta;
+mod wma;
+mod std_error_bands;
+mod schaff_trend_cycle;
+mod force_index;
+mod min_max;
+mod elder_ray;
+mod kama;
+mod vortex;
+mod ppo;
+mod mass_index;
+mod cmo;
 
 //--------------------------------------------------------------------------------------------------
-pub use price_diff::PriceDifference;
+pub use price_diff::{PriceChange, PriceDifference};
 pub use windowed_sma::WindowedSMA;
 pub use max_price::MaxPrice;
 pub use min_price::MinPrice;
+pub use cumulative_return::CumulativeReturn;
+pub use zscore::ZScore;
+pub use williams_r::WilliamsR;
+pub use ema::Ema;
+pub use trix::Trix;
+pub use cci::Cci;
+pub use atr::Atr;
+pub use keltner::KeltnerChannels;
+pub use donchian::DonchianChannel;
+pub use annualized_volatility::AnnualizedVolatility;
+pub use parabolic_sar::ParabolicSar;
+pub use linreg_slope::LinRegSlope;
+pub use volatility::Volatility;
+pub use bollinger::BollingerBands;
+pub use chaikin_money_flow::ChaikinMoneyFlow;
+pub use rsi::Rsi;
+pub use spec::SignalSpec;
+pub use money_flow_index::MoneyFlowIndex;
+pub use dpo::Dpo;
+pub use aroon::Aroon;
+pub use hull_ma::HullMA;
+pub use rate_of_change::RateOfChange;
+pub use coppock::CoppockCurve;
+pub use ulcer_index::UlcerIndex;
+pub use twap::Twap;
+pub use rolling_beta::RollingBeta;
+pub use wma::WeightedMA;
+pub use std_error_bands::StdErrorBands;
+pub use schaff_trend_cycle::SchaffTrendCycle;
+pub use force_index::ForceIndex;
+pub use min_max::MinMax;
+pub use elder_ray::ElderRay;
+pub use kama::Kama;
+pub use vortex::Vortex;
+pub use ppo::Ppo;
+pub use mass_index::MassIndex;
+pub use cmo::Cmo;
 //--------------------------------------------------------------------------------------------------
 
 ///
@@ -27,6 +103,141 @@ pub trait AsyncStockSignal {
     ///
     /// The signal (using the provided type) or `None` on error/invalid data.
     ///
-    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType>;
+    fn calculate(&self, series: &[f64]) -> impl Future<Output = Option<Self::SignalType>> + Send;
+}
+
+///
+/// A single price bar as reported by the data source.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ohlcv {
+    pub timestamp: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: u64,
+}
+
+///
+/// A trait for signals which need the full price bars rather than just the closing prices.
+///
+pub trait AsyncOhlcSignal {
+
+    ///
+    /// The signal's data type.
+    ///
+    type SignalType;
+
+    ///
+    /// Calculate the signal on the provided bars.
+    ///
+    /// # Returns
+    ///
+    /// The signal (using the provided type) or `None` on error/invalid data.
+    ///
+    fn calculate_ohlc(&self, bars: &[Ohlcv]) -> impl Future<Output = Option<Self::SignalType>> + Send;
 }
 
+///
+/// A trait for signals of a series relative to a second, benchmark series of the same length and dates.
+///
+pub trait AsyncPairSignal {
+
+    ///
+    /// The signal's data type.
+    ///
+    type SignalType;
+
+    ///
+    /// Calculate the signal on the provided series against the benchmark.
+    ///
+    /// # Returns
+    ///
+    /// The signal (using the provided type) or `None` on error/invalid data, including series of different lengths.
+    ///
+    fn calculate_pair(&self, series: &[f64], benchmark: &[f64]) -> impl Future<Output = Option<Self::SignalType>> + Send;
+}
+
+///
+/// Arithmetic mean of a non-empty series.
+///
+pub(crate) fn mean(series: &[f64]) -> f64 {
+    series.iter().sum::<f64>() / series.len() as f64
+}
+
+///
+/// Population standard deviation of a non-empty series.
+///
+pub(crate) fn std_dev(series: &[f64]) -> f64 {
+    let mean = mean(series);
+    let variance = series.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / series.len() as f64;
+    variance.sqrt()
+}
+
+///
+/// `f` of each full window of the series in turn, the iteration the rolling signals share: `None` for an empty series
+/// or a zero window and no values for a series shorter than the window. Along with each window `f` is handed the value
+/// which dropped out of it since the previous one, `None` for the first, so that it can roll a sum forward rather
+/// than recompute it.
+///
+pub(crate) fn rolling<T>(series: &[f64], window: usize, mut f: impl FnMut(&[f64], Option<f64>) -> T) -> Option<Vec<T>> {
+    if series.is_empty() || window == 0 {
+        return None;
+    }
+    Some(series.windows(window).enumerate().map(|(i, values)| f(values, i.checked_sub(1).map(|i| series[i]))).collect())
+}
+
+///
+/// A window function for `rolling` giving the sum of each window, rolled forward one value at a time.
+///
+pub(crate) fn rolled_sum() -> impl FnMut(&[f64], Option<f64>) -> f64 {
+    let mut sum = 0.0;
+    move |window, dropped| {
+        sum = match dropped {
+            Some(old) => sum + (window[window.len() - 1] - old),
+            None => window.iter().sum(),
+        };
+        sum
+    }
+}
+
+///
+/// A window function for `rolling` giving the mean and population standard deviation of each window. Both are rolled
+/// forward with Welford's update, which avoids the cancellation of subtracting large sums of squares.
+///
+pub(crate) fn rolled_mean_std() -> impl FnMut(&[f64], Option<f64>) -> (f64, f64) {
+    let (mut mean, mut m2) = (0.0, 0.0);
+    move |window, dropped| {
+        let n = window.len() as f64;
+        match dropped {
+            Some(old) => {
+                let new = window[window.len() - 1];
+                let prev_mean = mean;
+                mean += (new - old) / n;
+                // rounding can leave a flat window a hair below zero
+                m2 = (m2 + (new - old) * (new - mean + old - prev_mean)).max(0.0);
+            }
+            None => {
+                mean = self::mean(window);
+                m2 = window.iter().map(|v| (v - mean).powi(2)).sum();
+            }
+        }
+        (mean, (m2 / n).sqrt())
+    }
+}
+
+///
+/// The weighted mean of each full window, the latest value weighted `window` down to 1 for the oldest, normalized by
+/// the triangular number `window * (window + 1) / 2`.
+///
+pub(crate) fn weighted_means(series: &[f64], window: usize) -> Vec<f64> {
+    if window == 0 || series.len() < window {
+        return vec![];
+    }
+    let total = (window * (window + 1) / 2) as f64;
+    series
+        .windows(window)
+        .map(|values| values.iter().zip(1..).map(|(value, weight)| value * weight as f64).sum::<f64>() / total)
+        .collect()
+}
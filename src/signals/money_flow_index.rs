@@ -0,0 +1,55 @@
+use super::{AsyncOhlcSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoneyFlowIndex(usize);
+
+impl MoneyFlowIndex {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Money Flow Index: a volume weighted RSI. The raw money flow `typical price * volume` of each bar counts as positive
+/// when the typical price `(high + low + close) / 3` rose from the previous bar and negative when it fell, and over
+/// each trailing window `MFI = 100 - 100 / (1 + positive / negative)`. A window without negative flow is 100, one
+/// without any flow 50. Needs more bars than the window, the first only serving as the reference for the second.
+///
+impl AsyncOhlcSignal for MoneyFlowIndex {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        if self.0 == 0 || bars.len() <= self.0 {
+            return None;
+        }
+        let typical: Vec<f64> = bars.iter().map(|bar| (bar.high + bar.low + bar.close) / 3.0).collect();
+        let flows: Vec<(f64, f64)> = typical
+            .windows(2)
+            .zip(&bars[1..])
+            .map(|(pair, bar)| {
+                let flow = pair[1] * bar.volume as f64;
+                if pair[1] > pair[0] {
+                    (flow, 0.0)
+                } else if pair[1] < pair[0] {
+                    (0.0, flow)
+                } else {
+                    (0.0, 0.0)
+                }
+            })
+            .collect();
+        Some(
+            flows
+                .windows(self.0)
+                .map(|window| {
+                    let positive: f64 = window.iter().map(|(positive, _)| positive).sum();
+                    let negative: f64 = window.iter().map(|(_, negative)| negative).sum();
+                    if negative == 0.0 {
+                        if positive == 0.0 { 50.0 } else { 100.0 }
+                    } else {
+                        100.0 - 100.0 / (1.0 + positive / negative)
+                    }
+                })
+                .collect(),
+        )
+    }
+}
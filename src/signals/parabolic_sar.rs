@@ -0,0 +1,66 @@
+use super::{AsyncOhlcSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParabolicSar {
+    step: f64,
+    max_step: f64,
+}
+
+impl ParabolicSar {
+    pub fn new(step: f64, max_step: f64) -> Self {
+        Self { step, max_step }
+    }
+}
+
+///
+/// Parabolic SAR for every bar after the first. The trend starts in the direction of the first two closes, the
+/// acceleration factor grows by `step` (up to `max_step`) on each new extreme and the SAR jumps to the extreme when
+/// price crosses it, reversing the trend.
+///
+impl AsyncOhlcSignal for ParabolicSar {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        if bars.len() < 2 {
+            return None;
+        }
+        let mut rising = bars[1].close >= bars[0].close;
+        let (mut sar, mut extreme) = if rising {
+            (bars[0].low.min(bars[1].low), bars[0].high.max(bars[1].high))
+        } else {
+            (bars[0].high.max(bars[1].high), bars[0].low.min(bars[1].low))
+        };
+        let mut af = self.step;
+        let mut sars = vec![sar];
+        for i in 2..bars.len() {
+            let (prev, before, bar) = (bars[i - 1], bars[i - 2], bars[i]);
+            sar += af * (extreme - sar);
+            if rising {
+                // never above the lows of the two previous bars
+                sar = sar.min(prev.low).min(before.low);
+                if bar.low < sar {
+                    rising = false;
+                    sar = extreme;
+                    extreme = bar.low;
+                    af = self.step;
+                } else if bar.high > extreme {
+                    extreme = bar.high;
+                    af = (af + self.step).min(self.max_step);
+                }
+            } else {
+                sar = sar.max(prev.high).max(before.high);
+                if bar.high > sar {
+                    rising = true;
+                    sar = extreme;
+                    extreme = bar.high;
+                    af = self.step;
+                } else if bar.low < extreme {
+                    extreme = bar.low;
+                    af = (af + self.step).min(self.max_step);
+                }
+            }
+            sars.push(sar);
+        }
+        Some(sars)
+    }
+}
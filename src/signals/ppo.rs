@@ -0,0 +1,42 @@
+use super::{AsyncStockSignal, Ema};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ppo {
+    fast: usize,
+    slow: usize,
+    signal: usize,
+}
+
+impl Ppo {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self { fast, slow, signal }
+    }
+}
+
+///
+/// Percentage Price Oscillator as `(ppo, signal, histogram)`: the gap between the `fast` and `slow` EMAs as a percentage
+/// of the slow one, so that it compares across price levels, the `signal` EMA of that and the line less the signal.
+/// The three are aligned to the signal line, ending at the latest price; a zero slow EMA gives 0. `None` unless `fast`
+/// is shorter than `slow` and the series has the `slow + signal - 1` points for a signal value.
+///
+impl AsyncStockSignal for Ppo {
+    type SignalType = (Vec<f64>, Vec<f64>, Vec<f64>);
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.fast == 0 || self.signal == 0 || self.fast >= self.slow || series.len() < self.slow + self.signal - 1 {
+            return None;
+        }
+        let fast = Ema::new(self.fast).calculate(series).await?;
+        let slow = Ema::new(self.slow).calculate(series).await?;
+        // the slow EMA starts later, both end at the latest price
+        let line: Vec<f64> = fast[fast.len() - slow.len()..]
+            .iter()
+            .zip(&slow)
+            .map(|(fast, slow)| if *slow == 0.0 { 0.0 } else { 100.0 * (fast - slow) / slow })
+            .collect();
+        let signal = Ema::new(self.signal).calculate(&line).await?;
+        let line = line[line.len() - signal.len()..].to_vec();
+        let histogram = line.iter().zip(&signal).map(|(line, signal)| line - signal).collect();
+        Some((line, signal, histogram))
+    }
+}
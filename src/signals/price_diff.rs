@@ -1,24 +1,34 @@
 use super::AsyncStockSignal;
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PriceDifference {}
 
+///
+/// The change over a series: `absolute` in price, `relative` as a fraction of the first price.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct PriceChange {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
 ///
 /// Calculates the absolute and relative difference between the beginning and ending of an f64 series. The relative difference is relative to the beginning.
 ///
 /// # Returns
 ///
-/// A tuple `(absolute, relative)` difference.
+/// The `PriceChange`, absolute and relative.
 ///
 impl AsyncStockSignal for PriceDifference {
-    type SignalType = (f64, f64);
+    type SignalType = PriceChange;
     async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
         if !series.is_empty() {
             // unwrap is safe here even if first == last
             let (first, last) = (series.first().unwrap(), series.last().unwrap());
-            let abs_diff = last - first;
+            let absolute = last - first;
             let first = if *first == 0.0 { 1.0 } else { *first };
-            let rel_diff = abs_diff / first;
-            Some((abs_diff, rel_diff))
+            Some(PriceChange { absolute, relative: absolute / first })
         } else {
             None
         }
@@ -0,0 +1,31 @@
+use super::AsyncStockSignal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateOfChange(usize);
+
+impl RateOfChange {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Percentage change of each price over the price `window` points earlier, 0 where that price is 0. Series no longer
+/// than the window have no values.
+///
+impl AsyncStockSignal for RateOfChange {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if series.is_empty() || self.0 == 0 {
+            return None;
+        }
+        Some(
+            series
+                .iter()
+                .zip(series.iter().skip(self.0))
+                .map(|(old, new)| if *old == 0.0 { 0.0 } else { 100.0 * (new - old) / old })
+                .collect(),
+        )
+    }
+}
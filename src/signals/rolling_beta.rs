@@ -0,0 +1,37 @@
+use super::{mean, AsyncPairSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollingBeta(usize);
+
+impl RollingBeta {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Beta of aligned returns against the benchmark's over each trailing window: their covariance over the variance of the
+/// benchmark's, so 2 for returns moving twice as far as the benchmark. A window over which the benchmark doesn't move
+/// has a beta of 0.
+///
+impl AsyncPairSignal for RollingBeta {
+    type SignalType = Vec<f64>;
+    async fn calculate_pair(&self, series: &[f64], benchmark: &[f64]) -> Option<Self::SignalType> {
+        if series.is_empty() || self.0 < 2 || series.len() != benchmark.len() {
+            return None;
+        }
+        Some(
+            series
+                .windows(self.0)
+                .zip(benchmark.windows(self.0))
+                .map(|(returns, benchmark)| {
+                    let (mean_returns, mean_benchmark) = (mean(returns), mean(benchmark));
+                    let covariance: f64 = returns.iter().zip(benchmark).map(|(r, b)| (r - mean_returns) * (b - mean_benchmark)).sum();
+                    let variance: f64 = benchmark.iter().map(|b| (b - mean_benchmark).powi(2)).sum();
+                    if variance == 0.0 { 0.0 } else { covariance / variance }
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,45 @@
+use super::AsyncStockSignal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rsi(usize);
+
+impl Rsi {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Relative Strength Index with Wilder's smoothing, from 0 to 100, for every close after the first window of changes.
+/// Only gains gives 100, only losses 0 and a flat window 50.
+///
+impl AsyncStockSignal for Rsi {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if series.is_empty() || self.0 == 0 {
+            return None;
+        }
+        if series.len() <= self.0 {
+            return Some(vec![]);
+        }
+        let changes: Vec<f64> = series.windows(2).map(|w| w[1] - w[0]).collect();
+        let period = self.0 as f64;
+        let mut gain = changes[..self.0].iter().map(|c| c.max(0.0)).sum::<f64>() / period;
+        let mut loss = changes[..self.0].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period;
+        let rsi = |gain: f64, loss: f64| {
+            if loss == 0.0 {
+                if gain == 0.0 { 50.0 } else { 100.0 }
+            } else {
+                100.0 - 100.0 / (1.0 + gain / loss)
+            }
+        };
+        let mut values = vec![rsi(gain, loss)];
+        for change in &changes[self.0..] {
+            gain = (gain * (period - 1.0) + change.max(0.0)) / period;
+            loss = (loss * (period - 1.0) + (-change).max(0.0)) / period;
+            values.push(rsi(gain, loss));
+        }
+        Some(values)
+    }
+}
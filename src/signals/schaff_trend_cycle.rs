@@ -0,0 +1,58 @@
+use super::{AsyncStockSignal, Ema};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchaffTrendCycle {
+    fast: usize,
+    slow: usize,
+    cycle: usize,
+}
+
+impl SchaffTrendCycle {
+    pub fn new(fast: usize, slow: usize, cycle: usize) -> Self {
+        Self { fast, slow, cycle }
+    }
+}
+
+///
+/// Where the latest of each trailing `window` points sits in the window's range, 0 to 100, half way smoothed towards
+/// the previous value. A flat window repeats the previous value, 0 at the start.
+///
+fn smoothed_stochastic(series: &[f64], window: usize) -> Vec<f64> {
+    let mut smoothed: Option<f64> = None;
+    let mut stochastic = 0.0;
+    series
+        .windows(window)
+        .map(|window| {
+            let low = window.iter().copied().fold(f64::INFINITY, f64::min);
+            let high = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            if high > low {
+                stochastic = 100.0 * (window[window.len() - 1] - low) / (high - low);
+            }
+            let value = smoothed.map_or(stochastic, |previous| previous + 0.5 * (stochastic - previous));
+            smoothed = Some(value);
+            value
+        })
+        .collect()
+}
+
+///
+/// Schaff Trend Cycle: the MACD line (the `fast` less the `slow` EMA) put through a smoothed stochastic over `cycle`
+/// points twice, bounded 0 to 100. `None` unless the series is long enough for at least one value, i.e. `slow +
+/// 2 * (cycle - 1)` points.
+///
+impl AsyncStockSignal for SchaffTrendCycle {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.fast == 0 || self.slow == 0 || self.cycle == 0 {
+            return None;
+        }
+        let fast = Ema::new(self.fast).calculate(series).await?;
+        let slow = Ema::new(self.slow).calculate(series).await?;
+        // both end at the latest price, the shorter EMA just starts earlier
+        let len = fast.len().min(slow.len());
+        let macd: Vec<f64> = fast[fast.len() - len..].iter().zip(&slow[slow.len() - len..]).map(|(f, s)| f - s).collect();
+        let cycle = smoothed_stochastic(&smoothed_stochastic(&macd, self.cycle), self.cycle);
+        if cycle.is_empty() { None } else { Some(cycle) }
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use super::{
+    AnnualizedVolatility, Aroon, Atr, BollingerBands, Cci, ChaikinMoneyFlow, Cmo, CoppockCurve, DonchianChannel,
+    Dpo, ElderRay, Ema, ForceIndex, HullMA, Kama, KeltnerChannels, LinRegSlope, MassIndex, MoneyFlowIndex,
+    ParabolicSar, Ppo, RateOfChange, RollingBeta, Rsi, SchaffTrendCycle, StdErrorBands, Trix, Twap, UlcerIndex,
+    Volatility, Vortex, WeightedMA, WilliamsR, WindowedSMA, ZScore,
+};
+
+///
+/// Any signal with its parameters, so that the signals to run can be listed in a config file. Serialized with the
+/// signal's name as the key, e.g. `{"ema": 12}` or `{"bollinger": {"window": 20, "multiplier": 2.0}}`; signals
+/// without parameters are just their name.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalSpec {
+    PriceDifference,
+    MaxPrice,
+    MinPrice,
+    MinMax,
+    CumulativeReturn,
+    Sma(WindowedSMA),
+    Wma(WeightedMA),
+    Ema(Ema),
+    Hull(HullMA),
+    Kama(Kama),
+    Trix(Trix),
+    RateOfChange(RateOfChange),
+    Coppock(CoppockCurve),
+    Ppo(Ppo),
+    Stc(SchaffTrendCycle),
+    ZScore(ZScore),
+    WilliamsR(WilliamsR),
+    Cci(Cci),
+    Rsi(Rsi),
+    Cmo(Cmo),
+    Volatility(Volatility),
+    AnnualizedVolatility(AnnualizedVolatility),
+    UlcerIndex(UlcerIndex),
+    Bollinger(BollingerBands),
+    StdErrorBands(StdErrorBands),
+    Donchian(DonchianChannel),
+    Aroon(Aroon),
+    LinRegSlope(LinRegSlope),
+    RollingBeta(RollingBeta),
+    Dpo(Dpo),
+    Atr(Atr),
+    Keltner(KeltnerChannels),
+    ParabolicSar(ParabolicSar),
+    ChaikinMoneyFlow(ChaikinMoneyFlow),
+    Twap(Twap),
+    MoneyFlowIndex(MoneyFlowIndex),
+    ForceIndex(ForceIndex),
+    ElderRay(ElderRay),
+    Vortex(Vortex),
+    MassIndex(MassIndex),
+}
@@ -0,0 +1,41 @@
+use super::{mean, AsyncStockSignal, LinRegSlope};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StdErrorBands {
+    window: usize,
+    num_err: f64,
+}
+
+impl StdErrorBands {
+    pub fn new(window_size: usize, num_err: f64) -> Self {
+        Self { window: window_size, num_err }
+    }
+}
+
+///
+/// Standard Error Bands as `(lower, middle, upper)`: the least-squares line through each trailing window, at the latest
+/// price, offset either side by `num_err` standard errors of the estimate `sqrt(sum of squared residuals / (window - 2))`.
+/// Two points always lie on their line, so a window of 2 has no error.
+///
+impl AsyncStockSignal for StdErrorBands {
+    type SignalType = Vec<(f64, f64, f64)>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        let slopes = LinRegSlope::new(self.window).calculate(series).await?;
+        let mean_x = (self.window as f64 - 1.0) / 2.0;
+        Some(
+            series
+                .windows(self.window)
+                .zip(slopes)
+                .map(|(window, slope)| {
+                    let mean_y = mean(window);
+                    let fitted = |x: usize| mean_y + slope * (x as f64 - mean_x);
+                    let squares: f64 = window.iter().enumerate().map(|(x, y)| (y - fitted(x)).powi(2)).sum();
+                    let error = if self.window > 2 { (squares / (self.window - 2) as f64).sqrt() } else { 0.0 };
+                    let middle = fitted(self.window - 1);
+                    (middle - self.num_err * error, middle, middle + self.num_err * error)
+                })
+                .collect(),
+        )
+    }
+}
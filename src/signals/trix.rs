@@ -0,0 +1,34 @@
+use super::{AsyncStockSignal, Ema};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trix(usize);
+
+impl Trix {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Percentage rate of change of a triple-smoothed EMA. Each smoothing pass consumes `window - 1` prices and the rate of
+/// change one more, so `None` is returned unless the series is long enough to produce at least one value.
+///
+impl AsyncStockSignal for Trix {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        let ema = Ema::new(self.0);
+        let single = ema.calculate(series).await?;
+        let double = ema.calculate(&single).await?;
+        let triple = ema.calculate(&double).await?;
+        if triple.len() < 2 {
+            return None;
+        }
+        Some(
+            triple
+                .windows(2)
+                .map(|w| if w[0] == 0.0 { 0.0 } else { 100.0 * (w[1] - w[0]) / w[0] })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,45 @@
+use super::{AsyncOhlcSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Twap(usize);
+
+impl Twap {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Time weighted average price: the typical price `(high + low + close) / 3` of each bar in the trailing window, weighted
+/// by the time since the bar before it. Evenly spaced bars reduce to their simple average, as does a window of bars
+/// sharing a timestamp. The first bar only serves to time the second, so the series must be longer than the window.
+///
+impl AsyncOhlcSignal for Twap {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        if self.0 == 0 || bars.len() <= self.0 {
+            return None;
+        }
+        let timed: Vec<(f64, f64)> = bars
+            .windows(2)
+            .map(|pair| {
+                let bar = &pair[1];
+                ((bar.high + bar.low + bar.close) / 3.0, bar.timestamp.saturating_sub(pair[0].timestamp) as f64)
+            })
+            .collect();
+        Some(
+            timed
+                .windows(self.0)
+                .map(|window| {
+                    let duration: f64 = window.iter().map(|(_, duration)| duration).sum();
+                    if duration == 0.0 {
+                        window.iter().map(|(price, _)| price).sum::<f64>() / self.0 as f64
+                    } else {
+                        window.iter().map(|(price, duration)| price * duration).sum::<f64>() / duration
+                    }
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,36 @@
+use super::{rolling, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UlcerIndex(usize);
+
+impl UlcerIndex {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Ulcer Index: the root mean square of the percentage drawdowns from the running maximum over each trailing window.
+/// Only falls count, so unlike the volatility a steady climb scores 0. A price below a running maximum of 0 counts as
+/// no drawdown.
+///
+impl AsyncStockSignal for UlcerIndex {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.0 < 2 {
+            return None;
+        }
+        rolling(series, self.0, |window, _| {
+            let mut peak = f64::MIN;
+            let squares: f64 = window
+                .iter()
+                .map(|price| {
+                    peak = peak.max(*price);
+                    if peak == 0.0 { 0.0 } else { (100.0 * (price - peak) / peak).powi(2) }
+                })
+                .sum();
+            (squares / self.0 as f64).sqrt()
+        })
+    }
+}
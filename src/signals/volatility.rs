@@ -0,0 +1,25 @@
+use super::{rolled_mean_std, rolling, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Volatility(usize);
+
+impl Volatility {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Rolling population standard deviation of the closes over each trailing window.
+///
+impl AsyncStockSignal for Volatility {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.0 < 2 {
+            return None;
+        }
+        let mut stats = rolled_mean_std();
+        rolling(series, self.0, |window, dropped| stats(window, dropped).1)
+    }
+}
@@ -0,0 +1,36 @@
+use super::atr::true_range;
+use super::{rolled_sum, rolling, AsyncOhlcSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vortex(usize);
+
+impl Vortex {
+    pub fn new(period: usize) -> Self {
+        Self(period)
+    }
+}
+
+///
+/// Vortex Indicator as `(VI+, VI-)`: the sums of each bar's high less the previous low, and of the previous high less
+/// its low, over `period` bars, each divided by the sum of the true ranges. VI+ leads in an uptrend and VI- in a
+/// downtrend. A window without any true range is `(0.0, 0.0)`. `None` for a zero period or no more bars than it.
+///
+impl AsyncOhlcSignal for Vortex {
+    type SignalType = Vec<(f64, f64)>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<(f64, f64)>> {
+        if self.0 == 0 || bars.len() <= self.0 {
+            return None;
+        }
+        let (plus, minus): (Vec<f64>, Vec<f64>) = bars.windows(2).map(|w| ((w[1].high - w[0].low).abs(), (w[1].low - w[0].high).abs())).unzip();
+        let ranges = rolling(&true_range(bars), self.0, rolled_sum())?;
+        Some(
+            rolling(&plus, self.0, rolled_sum())?
+                .into_iter()
+                .zip(rolling(&minus, self.0, rolled_sum())?)
+                .zip(ranges)
+                .map(|((plus, minus), range)| if range == 0.0 { (0.0, 0.0) } else { (plus / range, minus / range) })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,52 @@
+use super::{AsyncOhlcSignal, AsyncStockSignal, Ohlcv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WilliamsR(usize);
+
+impl WilliamsR {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+
+    fn williams_r(&self, highs: &[f64], lows: &[f64], closes: &[f64]) -> Option<Vec<f64>> {
+        if closes.is_empty() || self.0 == 0 {
+            return None;
+        }
+        Some(
+            (self.0..=closes.len())
+                .map(|end| {
+                    let start = end - self.0;
+                    let highest = highs[start..end].iter().fold(f64::MIN, |acc, h| acc.max(*h));
+                    let lowest = lows[start..end].iter().fold(f64::MAX, |acc, l| acc.min(*l));
+                    if highest == lowest {
+                        0.0
+                    } else {
+                        -100.0 * (highest - closes[end - 1]) / (highest - lowest)
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+///
+/// Williams %R over a lookback window, ranging from -100 (close at the lowest low) to 0 (close at the highest high).
+/// Without bars the closes stand in for the highs and lows. A flat window yields `0.0`.
+///
+impl AsyncStockSignal for WilliamsR {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        self.williams_r(series, series, series)
+    }
+}
+
+impl AsyncOhlcSignal for WilliamsR {
+    type SignalType = Vec<f64>;
+    async fn calculate_ohlc(&self, bars: &[Ohlcv]) -> Option<Vec<f64>> {
+        let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+        let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+        let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+        self.williams_r(&highs, &lows, &closes)
+    }
+}
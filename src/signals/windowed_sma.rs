@@ -1,28 +1,35 @@
-use super::AsyncStockSignal;
+use super::{rolled_sum, rolling, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
 
-pub struct WindowedSMA(usize);
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowedSMA {
+    window: usize,
+}
 
 impl WindowedSMA {
-    pub(crate) fn new(window_size: usize) -> Self {
-        Self(window_size)
+    pub fn new(window_size: usize) -> Self {
+        Self { window: window_size }
+    }
+
+    ///
+    /// The number of prices averaged for each point.
+    ///
+    pub fn window(&self) -> usize {
+        self.window
     }
 }
 
 ///
-/// Window function to create a simple moving average
+/// Window function to create a simple moving average. The window sum is rolled forward rather than recomputed, so
+/// each point costs the same whatever the window size.
 ///
 impl AsyncStockSignal for WindowedSMA {
     type SignalType = Vec<f64>;
     async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
-        if !series.is_empty() && self.0 > 1 {
-            Some(
-                series
-                    .windows(self.0)
-                    .map(|w| w.iter().sum::<f64>() / w.len() as f64)
-                    .collect(),
-            )
-        } else {
-            None
+        if self.window < 2 {
+            return None;
         }
+        let mut sum = rolled_sum();
+        rolling(series, self.window, |window, dropped| sum(window, dropped) / self.window as f64)
     }
 }
@@ -0,0 +1,25 @@
+use super::{weighted_means, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightedMA(usize);
+
+impl WeightedMA {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Weighted moving average: the latest price in each window weighted `window` down to 1 for the oldest, normalized by
+/// the triangular number `window * (window + 1) / 2`. Like the SMA, a series shorter than the window has no values.
+///
+impl AsyncStockSignal for WeightedMA {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if series.is_empty() || self.0 < 2 {
+            return None;
+        }
+        Some(weighted_means(series, self.0))
+    }
+}
@@ -0,0 +1,35 @@
+use super::{rolled_mean_std, rolling, AsyncStockSignal};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZScore(usize);
+
+impl ZScore {
+    pub fn new(window_size: usize) -> Self {
+        Self(window_size)
+    }
+}
+
+///
+/// Rolling z-score of the latest price in each window relative to that window's mean and (population) standard deviation.
+/// A window with zero standard deviation yields `0.0` rather than dividing by zero. The mean and deviation are rolled
+/// forward rather than recomputed for each window.
+///
+impl AsyncStockSignal for ZScore {
+    type SignalType = Vec<f64>;
+    async fn calculate(&self, series: &[f64]) -> Option<Self::SignalType> {
+        if self.0 < 2 {
+            return None;
+        }
+        let mut stats = rolled_mean_std();
+        rolling(series, self.0, |w, dropped| {
+            let (mean, std_dev) = stats(w, dropped);
+            if std_dev == 0.0 {
+                0.0
+            } else {
+                // unwrap is safe here as windows are never empty
+                (w.last().unwrap() - mean) / std_dev
+            }
+        })
+    }
+}
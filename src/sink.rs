@@ -0,0 +1,393 @@
+use std::io::{Error, Write};
+use std::path::Path;
+use std::sync::Arc;
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, TimestampMicrosecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::DateTime;
+use parquet::arrow::ArrowWriter;
+use rusqlite::{params, Connection};
+use crate::{Column, StockRow};
+
+///
+/// A destination for calculated rows. Rows are written as they become available and the sink is finished once the run
+/// is complete.
+///
+pub trait OutputSink {
+
+    ///
+    /// Write a single row.
+    ///
+    fn write_row(&mut self, row: &StockRow) -> std::io::Result<()>;
+
+    ///
+    /// Complete the output, e.g. flush any buffered data. No rows are written after this.
+    ///
+    fn finish(&mut self) -> std::io::Result<()>;
+}
+
+///
+/// Rows as comma separated values restricted to the sink's columns, under a matching header. The header is written even when there are no rows.
+///
+pub struct CsvSink<W: Write> {
+    writer: W,
+    columns: Vec<Column>,
+    precision: usize,
+    header_written: bool,
+}
+
+impl<W: Write> CsvSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_columns(writer, Column::DEFAULT.to_vec())
+    }
+
+    pub fn with_columns(writer: W, columns: Vec<Column>) -> Self {
+        Self { writer, columns, precision: StockRow::DEFAULT_PRECISION, header_written: false }
+    }
+
+    ///
+    /// Leave out the header, e.g. when appending to a file which already has one.
+    ///
+    pub fn without_header(mut self) -> Self {
+        self.header_written = true;
+        self
+    }
+
+    ///
+    /// Round the numeric columns to `precision` decimal places.
+    ///
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    fn write_header(&mut self) -> std::io::Result<()> {
+        if !self.header_written {
+            writeln!(self.writer, "{}", StockRow::csv_header(&self.columns))?;
+            self.header_written = true;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> OutputSink for CsvSink<W> {
+    fn write_row(&mut self, row: &StockRow) -> std::io::Result<()> {
+        self.write_header()?;
+        writeln!(self.writer, "{}", row.to_csv_columns(&self.columns, self.precision))
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.write_header()?;
+        self.writer.flush()
+    }
+}
+
+///
+/// Rows as newline delimited JSON objects, one per row, so they can be consumed as they arrive. Only the fields of the
+/// sink's columns are included.
+///
+pub struct JsonSink<W: Write> {
+    writer: W,
+    columns: Vec<Column>,
+}
+
+impl<W: Write> JsonSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_columns(writer, Column::ALL.to_vec())
+    }
+
+    pub fn with_columns(writer: W, columns: Vec<Column>) -> Self {
+        Self { writer, columns }
+    }
+}
+
+impl<W: Write> OutputSink for JsonSink<W> {
+    fn write_row(&mut self, row: &StockRow) -> std::io::Result<()> {
+        let mut fields = match serde_json::to_value(row)? {
+            serde_json::Value::Object(fields) => fields,
+            _ => unreachable!("a StockRow always serializes to an object"),
+        };
+        let selected: serde_json::Map<String, serde_json::Value> = self.columns
+            .iter()
+            .filter_map(|column| fields.remove_entry(column.field()))
+            .collect();
+        serde_json::to_writer(&mut self.writer, &selected)?;
+        writeln!(self.writer)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+///
+/// Rows as an aligned plain text table for reading in a terminal: a header, a rule and one line per row, with the numeric
+/// columns right-aligned. Column widths depend on every row, so rows are buffered until the sink is finished.
+///
+pub struct TableSink<W: Write> {
+    writer: W,
+    columns: Vec<Column>,
+    precision: usize,
+    rows: Vec<Vec<String>>,
+}
+
+impl<W: Write> TableSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_columns(writer, Column::DEFAULT.to_vec())
+    }
+
+    pub fn with_columns(writer: W, columns: Vec<Column>) -> Self {
+        Self { writer, columns, precision: StockRow::DEFAULT_PRECISION, rows: vec![] }
+    }
+
+    ///
+    /// Round the numeric columns to `precision` decimal places.
+    ///
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    fn write_line(&mut self, cells: &[String], widths: &[usize]) -> std::io::Result<()> {
+        let padded: Vec<String> = self.columns
+            .iter()
+            .zip(cells.iter().zip(widths))
+            .map(|(column, (cell, width))| {
+                if column.is_numeric() { format!("{:>1$}", cell, width) } else { format!("{:<1$}", cell, width) }
+            })
+            .collect();
+        writeln!(self.writer, "{}", padded.join(" | ").trim_end())
+    }
+}
+
+impl<W: Write> OutputSink for TableSink<W> {
+    fn write_row(&mut self, row: &StockRow) -> std::io::Result<()> {
+        self.rows.push(self.columns.iter().map(|column| row.cell(*column, self.precision)).collect());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        let header: Vec<String> = self.columns.iter().map(|column| column.header().to_string()).collect();
+        let widths: Vec<usize> = (0..self.columns.len())
+            .map(|i| self.rows.iter().chain([&header]).map(|cells| cells[i].chars().count()).max().unwrap_or(0))
+            .collect();
+        self.write_line(&header, &widths)?;
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        writeln!(self.writer, "{}", rule.join("-+-"))?;
+        for cells in std::mem::take(&mut self.rows) {
+            self.write_line(&cells, &widths)?;
+        }
+        self.writer.flush()
+    }
+}
+
+///
+/// Rows as a columnar Parquet file. Parquet is written in one go, so rows are buffered until the sink is finished. The
+/// period start is stored as a UTC timestamp, the symbol as utf8 and every other column as a nullable float64.
+///
+pub struct ParquetSink<W: Write + Send> {
+    writer: Option<W>,
+    columns: Vec<Column>,
+    rows: Vec<StockRow>,
+}
+
+impl<W: Write + Send> ParquetSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_columns(writer, Column::ALL.to_vec())
+    }
+
+    pub fn with_columns(writer: W, columns: Vec<Column>) -> Self {
+        Self { writer: Some(writer), columns, rows: vec![] }
+    }
+
+    fn schema(&self) -> Schema {
+        Schema::new(
+            self.columns
+                .iter()
+                .map(|column| match column {
+                    Column::PeriodStart => Field::new(column.field(), DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), false),
+                    Column::Symbol => Field::new(column.field(), DataType::Utf8, false),
+                    Column::LastQuote => Field::new(column.field(), DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), true),
+                    _ => Field::new(column.field(), DataType::Float64, true),
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn array(&self, column: Column) -> std::io::Result<ArrayRef> {
+        Ok(match column {
+            Column::PeriodStart => {
+                let timestamps = self.rows
+                    .iter()
+                    .map(|row| DateTime::parse_from_rfc3339(&row.period_start).map(|t| t.timestamp_micros()))
+                    .collect::<Result<Vec<i64>, _>>()
+                    .map_err(Error::other)?;
+                Arc::new(TimestampMicrosecondArray::from(timestamps).with_timezone("UTC"))
+            }
+            Column::Symbol => Arc::new(StringArray::from_iter_values(self.rows.iter().map(|row| row.symbol.as_str()))),
+            Column::LastQuote => {
+                let timestamps = self.rows
+                    .iter()
+                    .map(|row| row.last_quote.as_deref().map(|at| DateTime::parse_from_rfc3339(at).map(|t| t.timestamp_micros())).transpose())
+                    .collect::<Result<Vec<Option<i64>>, _>>()
+                    .map_err(Error::other)?;
+                Arc::new(TimestampMicrosecondArray::from(timestamps).with_timezone("UTC"))
+            }
+            _ => Arc::new(Float64Array::from(self.rows.iter().map(|row| row.value(column)).collect::<Vec<_>>())),
+        })
+    }
+}
+
+impl<W: Write + Send> OutputSink for ParquetSink<W> {
+    fn write_row(&mut self, row: &StockRow) -> std::io::Result<()> {
+        self.rows.push(row.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+        let schema = Arc::new(self.schema());
+        let arrays = self.columns.iter().map(|column| self.array(*column)).collect::<std::io::Result<Vec<_>>>()?;
+        let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(Error::other)?;
+        let mut parquet = ArrowWriter::try_new(writer, schema, None).map_err(Error::other)?;
+        parquet.write(&batch).map_err(Error::other)?;
+        parquet.close().map_err(Error::other)?;
+        Ok(())
+    }
+}
+
+///
+/// Rows upserted into the `signals` table of a SQLite database, keyed by `(symbol, period_start)`, so that repeated runs
+/// over the same period update the existing rows rather than duplicating them. The table is created if absent.
+///
+pub struct SqliteSink {
+    connection: Connection,
+}
+
+impl SqliteSink {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let connection = Connection::open(path).map_err(Error::other)?;
+        connection.execute_batch(Self::CREATE_TABLE).map_err(Error::other)?;
+        Self::add_missing_columns(&connection)?;
+        Self::allow_null_signals(&connection)?;
+        Ok(Self { connection })
+    }
+
+    const CREATE_TABLE: &'static str = "CREATE TABLE IF NOT EXISTS signals (
+        symbol TEXT NOT NULL,
+        period_start TEXT NOT NULL,
+        price REAL NOT NULL,
+        pct_change REAL,
+        period_min REAL,
+        period_max REAL,
+        last_sma REAL,
+        cum_return REAL,
+        annualized_vol REAL,
+        rsi REAL,
+        rel_strength REAL,
+        last_quote TEXT,
+        PRIMARY KEY (symbol, period_start)
+    )";
+
+    ///
+    /// Columns added to the table since it was first written, added to a table created without them.
+    ///
+    const ADDED_COLUMNS: [(&'static str, &'static str); 3] = [("rsi", "REAL"), ("rel_strength", "REAL"), ("last_quote", "TEXT")];
+
+    fn add_missing_columns(connection: &Connection) -> std::io::Result<()> {
+        let mut statement = connection.prepare("SELECT name FROM pragma_table_info('signals')").map_err(Error::other)?;
+        let existing = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|names| names.collect::<Result<Vec<_>, _>>())
+            .map_err(Error::other)?;
+        for (column, kind) in Self::ADDED_COLUMNS.iter().filter(|(column, _)| !existing.iter().any(|name| name == column)) {
+            connection.execute_batch(&format!("ALTER TABLE signals ADD COLUMN {} {}", column, kind)).map_err(Error::other)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Rebuild a table from before signals could be missing, whose signal columns were `NOT NULL`, with the current
+    /// schema. SQLite can't drop a constraint in place, so the rows are copied across.
+    ///
+    fn allow_null_signals(connection: &Connection) -> std::io::Result<()> {
+        let constrained: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('signals') WHERE \"notnull\" AND name NOT IN ('symbol', 'period_start', 'price')",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(Error::other)?;
+        if constrained == 0 {
+            return Ok(());
+        }
+        let columns = "symbol, period_start, price, pct_change, period_min, period_max, last_sma, cum_return, annualized_vol, rsi, rel_strength, last_quote";
+        connection
+            .execute_batch(&format!(
+                "BEGIN;
+                 ALTER TABLE signals RENAME TO signals_not_null;
+                 {};
+                 INSERT INTO signals ({columns}) SELECT {columns} FROM signals_not_null;
+                 DROP TABLE signals_not_null;
+                 COMMIT;",
+                Self::CREATE_TABLE,
+            ))
+            .map_err(Error::other)
+    }
+
+    ///
+    /// The latest period start and last quote stored for each symbol, the last quote `None` for rows written before
+    /// it was recorded.
+    ///
+    pub fn latest_period_starts(&self) -> std::io::Result<Vec<(String, String, Option<String>)>> {
+        let mut statement = self.connection
+            .prepare("SELECT symbol, MAX(period_start), MAX(last_quote) FROM signals GROUP BY symbol")
+            .map_err(Error::other)?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(Error::other)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Error::other)
+    }
+}
+
+impl OutputSink for SqliteSink {
+    fn write_row(&mut self, row: &StockRow) -> std::io::Result<()> {
+        self.connection
+            .execute(
+                "INSERT INTO signals (symbol, period_start, price, pct_change, period_min, period_max, last_sma, cum_return, annualized_vol, rsi, rel_strength, last_quote)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                 ON CONFLICT (symbol, period_start) DO UPDATE SET
+                    price = excluded.price,
+                    pct_change = excluded.pct_change,
+                    period_min = excluded.period_min,
+                    period_max = excluded.period_max,
+                    last_sma = excluded.last_sma,
+                    cum_return = excluded.cum_return,
+                    annualized_vol = excluded.annualized_vol,
+                    rsi = excluded.rsi,
+                    rel_strength = excluded.rel_strength,
+                    last_quote = excluded.last_quote",
+                params![
+                    row.symbol,
+                    row.period_start,
+                    row.price,
+                    row.pct_change,
+                    row.period_min,
+                    row.period_max,
+                    row.last_sma,
+                    row.cum_return,
+                    row.annualized_vol,
+                    row.rsi,
+                    row.rel_strength,
+                    row.last_quote,
+                ],
+            )
+            .map(|_| ())
+            .map_err(Error::other)
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
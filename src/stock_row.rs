@@ -0,0 +1,310 @@
+use std::str::FromStr;
+use serde::Serialize;
+
+///
+/// The columns a `StockRow` can be written with, in their default order.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    PeriodStart,
+    Symbol,
+    Price,
+    Change,
+    Min,
+    Max,
+    Sma,
+    CumReturn,
+    AnnualizedVol,
+    Rsi,
+    RelStrength,
+    LastQuote,
+}
+
+impl Column {
+    ///
+    /// The columns written when none are requested explicitly.
+    ///
+    pub const DEFAULT: [Column; 8] = [
+        Column::PeriodStart,
+        Column::Symbol,
+        Column::Price,
+        Column::Change,
+        Column::Min,
+        Column::Max,
+        Column::Sma,
+        Column::CumReturn,
+    ];
+
+    ///
+    /// Every column, in the default order.
+    ///
+    pub const ALL: [Column; 12] = [
+        Column::PeriodStart,
+        Column::Symbol,
+        Column::Price,
+        Column::Change,
+        Column::Min,
+        Column::Max,
+        Column::Sma,
+        Column::CumReturn,
+        Column::AnnualizedVol,
+        Column::Rsi,
+        Column::RelStrength,
+        Column::LastQuote,
+    ];
+
+    ///
+    /// The name used to select the column on the command line.
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::PeriodStart => "period_start",
+            Column::Symbol => "symbol",
+            Column::Price => "price",
+            Column::Change => "change",
+            Column::Min => "min",
+            Column::Max => "max",
+            Column::Sma => "sma",
+            Column::CumReturn => "cum_return",
+            Column::AnnualizedVol => "annualized_vol",
+            Column::Rsi => "rsi",
+            Column::RelStrength => "rel_strength",
+            Column::LastQuote => "last_quote",
+        }
+    }
+
+    ///
+    /// The `StockRow` field holding the column's value.
+    ///
+    pub fn field(&self) -> &'static str {
+        match self {
+            Column::PeriodStart => "period_start",
+            Column::Symbol => "symbol",
+            Column::Price => "price",
+            Column::Change => "pct_change",
+            Column::Min => "period_min",
+            Column::Max => "period_max",
+            Column::Sma => "last_sma",
+            Column::CumReturn => "cum_return",
+            Column::AnnualizedVol => "annualized_vol",
+            Column::Rsi => "rsi",
+            Column::RelStrength => "rel_strength",
+            Column::LastQuote => "last_quote",
+        }
+    }
+
+    ///
+    /// Whether the column holds a number rather than text.
+    ///
+    pub fn is_numeric(&self) -> bool {
+        !matches!(self, Column::PeriodStart | Column::Symbol | Column::LastQuote)
+    }
+
+    ///
+    /// The column headed `header` in CSV output.
+    ///
+    pub fn from_header(header: &str) -> Option<Column> {
+        Column::ALL.into_iter().find(|column| column.header() == header.trim())
+    }
+
+    ///
+    /// The column's heading in CSV and table output.
+    ///
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::PeriodStart => "period start",
+            Column::Symbol => "symbol",
+            Column::Price => "price",
+            Column::Change => "change %",
+            Column::Min => "min",
+            Column::Max => "max",
+            // the window is configurable, so the header doesn't name one
+            Column::Sma => "sma",
+            Column::CumReturn => "cum return %",
+            Column::AnnualizedVol => "annualized vol %",
+            Column::Rsi => "rsi",
+            Column::RelStrength => "rel strength",
+            Column::LastQuote => "last quote",
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        Column::ALL.into_iter().find(|column| column.name() == name).ok_or_else(|| {
+            let valid: Vec<&str> = Column::ALL.iter().map(Column::name).collect();
+            format!("unknown column '{}', expected one of: {}", s.trim(), valid.join(", "))
+        })
+    }
+}
+
+///
+/// The signals calculated for a single symbol over the requested period.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StockRow {
+    pub period_start: String,
+    pub symbol: String,
+    pub price: f64,
+    /// `None`, like the other signals, when it couldn't be calculated from the series, which is written as an empty
+    /// CSV cell or a JSON `null` rather than a misleading zero.
+    pub pct_change: Option<f64>,
+    pub period_min: Option<f64>,
+    pub period_max: Option<f64>,
+    pub last_sma: Option<f64>,
+    pub cum_return: Option<f64>,
+    pub annualized_vol: Option<f64>,
+    pub rsi: Option<f64>,
+    /// The symbol's growth over a benchmark's, `(1 + return) / (1 + benchmark return)`.
+    pub rel_strength: Option<f64>,
+    /// When the latest quote the signals were calculated on was made, which an incremental run picks up after.
+    pub last_quote: Option<String>,
+    /// The currency prices were converted into, written after each price instead of a dollar sign. The JSON, Parquet
+    /// and SQLite outputs hold plain numbers, with the conversion recorded in the run's metadata.
+    #[serde(skip)]
+    pub currency: Option<String>,
+}
+
+impl StockRow {
+    ///
+    /// Decimal places of the numeric CSV columns unless asked otherwise.
+    ///
+    pub const DEFAULT_PRECISION: usize = 2;
+
+    ///
+    /// The CSV header for the given columns, a heading for each cell `to_csv_columns` writes with them.
+    ///
+    pub fn csv_header(columns: &[Column]) -> String {
+        columns.iter().map(Column::header).collect::<Vec<_>>().join(",")
+    }
+
+    pub fn to_csv_row(&self) -> String {
+        self.to_csv_row_with_precision(Self::DEFAULT_PRECISION)
+    }
+
+    ///
+    /// The default columns as CSV with `precision` decimal places in every numeric column.
+    ///
+    pub fn to_csv_row_with_precision(&self, precision: usize) -> String {
+        self.to_csv_columns(&Column::DEFAULT, precision)
+    }
+
+    ///
+    /// The row as CSV restricted to the given columns, numbers rounded to `precision` decimal places. Signals which
+    /// weren't calculated are left empty.
+    ///
+    pub fn to_csv_columns(&self, columns: &[Column], precision: usize) -> String {
+        columns.iter().map(|column| self.cell(*column, precision)).collect::<Vec<_>>().join(",")
+    }
+
+    ///
+    /// Parse a row written by `to_csv_columns` with the same columns. Signals left out or written empty are `None`, a
+    /// price left out is zero, and the numbers are only as precise as they were written.
+    ///
+    pub fn from_csv_columns(line: &str, columns: &[Column]) -> Result<StockRow, String> {
+        let cells: Vec<&str> = line.split(',').collect();
+        if cells.len() != columns.len() {
+            return Err(format!("expected {} cells, got {}", columns.len(), cells.len()));
+        }
+        let mut row = StockRow {
+            period_start: String::new(),
+            symbol: String::new(),
+            price: 0.0,
+            pct_change: None,
+            period_min: None,
+            period_max: None,
+            last_sma: None,
+            cum_return: None,
+            annualized_vol: None,
+            rsi: None,
+            rel_strength: None,
+            last_quote: None,
+            currency: None,
+        };
+        for (column, cell) in columns.iter().zip(cells) {
+            let number = || {
+                let digits = cell.trim().trim_start_matches('$').trim_end_matches('%');
+                let digits = match digits.split_once(' ') {
+                    Some((amount, currency)) if currency.chars().all(|c| c.is_ascii_uppercase()) => amount,
+                    _ => digits,
+                };
+                digits.parse::<f64>().map_err(|_| format!("{} '{}' isn't a number", column.name(), cell))
+            };
+            let optional = || if cell.trim().is_empty() { Ok(None) } else { number().map(Some) };
+            match column {
+                Column::PeriodStart => row.period_start = cell.to_string(),
+                Column::Symbol => row.symbol = cell.to_string(),
+                Column::Price => row.price = number()?,
+                Column::Change => row.pct_change = optional()?,
+                Column::Min => row.period_min = optional()?,
+                Column::Max => row.period_max = optional()?,
+                Column::Sma => row.last_sma = optional()?,
+                Column::CumReturn => row.cum_return = optional()?.map(|v| v / 100.0),
+                Column::AnnualizedVol => row.annualized_vol = optional()?.map(|v| v / 100.0),
+                Column::Rsi => row.rsi = optional()?,
+                Column::RelStrength => row.rel_strength = optional()?,
+                Column::LastQuote => row.last_quote = Some(cell.to_string()).filter(|cell| !cell.trim().is_empty()),
+            }
+        }
+        Ok(row)
+    }
+
+    ///
+    /// Convert the prices into `currency` at `rate` units per dollar. Changes, returns and volatility are ratios and
+    /// stay as they are.
+    ///
+    pub fn convert(&mut self, rate: f64, currency: &str) {
+        self.currency = Some(currency.to_string());
+        self.price *= rate;
+        self.period_min = self.period_min.map(|min| min * rate);
+        self.period_max = self.period_max.map(|max| max * rate);
+        self.last_sma = self.last_sma.map(|sma| sma * rate);
+    }
+
+    ///
+    /// The raw value of a numeric column, or `None` for text columns and signals which weren't calculated.
+    ///
+    pub fn value(&self, column: Column) -> Option<f64> {
+        match column {
+            Column::PeriodStart | Column::Symbol | Column::LastQuote => None,
+            Column::Price => Some(self.price),
+            Column::Change => self.pct_change,
+            Column::Min => self.period_min,
+            Column::Max => self.period_max,
+            Column::Sma => self.last_sma,
+            Column::CumReturn => self.cum_return,
+            Column::AnnualizedVol => self.annualized_vol,
+            Column::Rsi => self.rsi,
+            Column::RelStrength => self.rel_strength,
+        }
+    }
+
+    ///
+    /// A single column formatted for display: prices in dollars, or followed by their currency once converted, and
+    /// changes as percentages, to `precision` decimal places. A signal which wasn't calculated is empty.
+    ///
+    pub fn cell(&self, column: Column, precision: usize) -> String {
+        let dollars = |v: f64| match &self.currency {
+            Some(currency) => format!("{:.*} {}", precision, v, currency),
+            None => format!("${:.*}", precision, v),
+        };
+        let percent = |v: f64| format!("{:.*}%", precision, v);
+        match column {
+            Column::PeriodStart => self.period_start.clone(),
+            Column::Symbol => self.symbol.clone(),
+            Column::Price => dollars(self.price),
+            Column::Change => self.pct_change.map(percent).unwrap_or_default(),
+            Column::Min => self.period_min.map(dollars).unwrap_or_default(),
+            Column::Max => self.period_max.map(dollars).unwrap_or_default(),
+            Column::Sma => self.last_sma.map(dollars).unwrap_or_default(),
+            Column::CumReturn => self.cum_return.map(|v| percent(v * 100.0)).unwrap_or_default(),
+            Column::AnnualizedVol => self.annualized_vol.map(|v| percent(v * 100.0)).unwrap_or_default(),
+            Column::Rsi => self.rsi.map(|v| format!("{:.*}", precision, v)).unwrap_or_default(),
+            Column::RelStrength => self.rel_strength.map(|v| format!("{:.*}", precision, v)).unwrap_or_default(),
+            Column::LastQuote => self.last_quote.clone().unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{self, Duration, Instant};
+
+///
+/// Limits how many fetches are in flight, backing off for the whole pipeline when the provider rate limits a request:
+/// each rate limited response halves the permits, down to one, and holds every new request back for a cooldown. Each
+/// success afterwards hands one permit back, until the limit is where it started.
+///
+#[derive(Debug)]
+pub struct Throttle {
+    semaphore: Arc<Semaphore>,
+    max: usize,
+    cooldown: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    limit: usize,
+    /// Permits taken away while they were held, forgotten as they are handed back instead of being released.
+    debt: usize,
+    resume_at: Option<Instant>,
+}
+
+///
+/// A permit to fetch, returned to the throttle on drop unless the limit shrank while it was held.
+///
+#[derive(Debug)]
+pub struct ThrottlePermit<'a> {
+    throttle: &'a Throttle,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Throttle {
+    pub fn new(max: usize, cooldown: Duration) -> Self {
+        let max = max.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max)),
+            max,
+            cooldown,
+            state: Mutex::new(State { limit: max, debt: 0, resume_at: None }),
+        }
+    }
+
+    ///
+    /// The number of fetches currently allowed in flight.
+    ///
+    pub fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit
+    }
+
+    ///
+    /// Wait for a permit, and then for any cooldown to pass.
+    ///
+    pub async fn acquire(&self) -> ThrottlePermit<'_> {
+        let permit = self.semaphore.clone().acquire_owned().await.expect("the throttle's semaphore is never closed");
+        let resume_at = self.state.lock().unwrap().resume_at;
+        if let Some(resume_at) = resume_at {
+            time::sleep_until(resume_at).await;
+        }
+        ThrottlePermit { throttle: self, permit: Some(permit) }
+    }
+
+    ///
+    /// Halve the limit and start a cooldown.
+    ///
+    pub fn rate_limited(&self) {
+        let mut state = self.state.lock().unwrap();
+        let target = (state.limit / 2).max(1);
+        let excess = state.limit - target;
+        let forgotten = self.semaphore.forget_permits(excess);
+        state.debt += excess - forgotten;
+        state.limit = target;
+        state.resume_at = Some(Instant::now() + self.cooldown);
+    }
+
+    ///
+    /// Grow the limit by one, back towards where it started.
+    ///
+    pub fn succeeded(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.limit == self.max {
+            return;
+        }
+        state.limit += 1;
+        if state.debt > 0 {
+            state.debt -= 1;
+        } else {
+            self.semaphore.add_permits(1);
+        }
+    }
+}
+
+impl Drop for ThrottlePermit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.throttle.state.lock().unwrap();
+        if let Some(permit) = self.permit.take() {
+            if state.debt > 0 {
+                state.debt -= 1;
+                permit.forget();
+            }
+        }
+    }
+}